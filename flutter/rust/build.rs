@@ -1,4 +1,9 @@
 fn main() {
+  // Only the bindings need the C++, `pure-rust` alone builds without it.
+  if std::env::var_os("CARGO_FEATURE_CPP").is_none() {
+    return;
+  }
+
   // Build fasttext C++
   let mut build = cc::Build::new();
   let compiler = build.get_compiler();
@@ -10,6 +10,7 @@ pub trait HasError {
   type ResultType;
 
   fn error(&self) -> *const std::os::raw::c_char;
+  fn error_code(&self) -> u32;
   fn result(&self) -> Self::ResultType;
 }
 
@@ -19,6 +20,7 @@ macro_rules! impl_has_error {
       type ResultType = $res;
 
       fn error(&self) -> *const std::os::raw::c_char { self.error }
+      fn error_code(&self) -> u32 { self.error_code }
       fn result(&self) -> Self::ResultType { self.result }
     }
   };
@@ -27,6 +29,18 @@ macro_rules! impl_has_error {
 impl_has_error!(FastTextResult, *mut fasttext_t);
 impl_has_error!(VoidResult, *mut std::os::raw::c_void);
 impl_has_error!(FastTextPredictionResult, *mut fasttext_prediction_t);
+impl_has_error!(FastTextPredictionsResult, *mut fasttext_predictions_t);
+impl_has_error!(TokenContributionsResult, *mut fasttext_token_contribution_t);
 impl_has_error!(FloatCharPairResult, *mut fasttext_float_char_pair_t);
 impl_has_error!(Int32Result, i32);
 impl_has_error!(IntResult, std::os::raw::c_int);
+impl_has_error!(Int64Result, i64);
+impl_has_error!(MatrixResult, fasttext_matrix_t);
+impl_has_error!(TokensResult, *mut fasttext_token_t);
+impl_has_error!(SubwordsResult, *mut fasttext_subword_t);
+impl_has_error!(NgramVectorsResult, *mut fasttext_ngram_vector_t);
+impl_has_error!(MeterResult, *mut fasttext_meter_t);
+impl_has_error!(ArgsResult, *mut fasttext_args_t);
+impl_has_error!(BoolResult, bool);
+impl_has_error!(AutotuneResult, fasttext_autotune_result_t);
+impl_has_error!(StringResult, *mut std::os::raw::c_char);
@@ -0,0 +1,899 @@
+use fasttext_bindings::bindings::{
+  fasttext_args_delete, fasttext_args_get_autotune_duration, fasttext_args_get_autotune_metric,
+  fasttext_args_get_autotune_model_size, fasttext_args_get_autotune_predictions,
+  fasttext_args_get_autotune_validation_file, fasttext_args_get_bucket, fasttext_args_get_cutoff,
+  fasttext_args_get_dim, fasttext_args_get_dsub, fasttext_args_get_epoch, fasttext_args_get_input,
+  fasttext_args_get_label, fasttext_args_get_loss, fasttext_args_get_lr,
+  fasttext_args_get_lr_update_rate, fasttext_args_get_maxn, fasttext_args_get_min_count,
+  fasttext_args_get_min_count_label, fasttext_args_get_minn, fasttext_args_get_model,
+  fasttext_args_get_neg, fasttext_args_get_output, fasttext_args_get_pretrained_vectors,
+  fasttext_args_get_qnorm, fasttext_args_get_qout, fasttext_args_get_retrain,
+  fasttext_args_get_save_output, fasttext_args_get_seed, fasttext_args_get_t,
+  fasttext_args_get_thread, fasttext_args_get_verbose, fasttext_args_get_word_ngrams,
+  fasttext_args_get_ws, fasttext_args_is_manual, fasttext_args_new, fasttext_args_parse,
+  fasttext_args_set_autotune_duration, fasttext_args_set_autotune_metric,
+  fasttext_args_set_autotune_model_size, fasttext_args_set_autotune_predictions,
+  fasttext_args_set_autotune_validation_file, fasttext_args_set_bucket, fasttext_args_set_cutoff,
+  fasttext_args_set_dim, fasttext_args_set_dsub, fasttext_args_set_epoch, fasttext_args_set_input,
+  fasttext_args_set_label, fasttext_args_set_loss, fasttext_args_set_lr,
+  fasttext_args_set_lr_update_rate, fasttext_args_set_manual, fasttext_args_set_maxn,
+  fasttext_args_set_min_count, fasttext_args_set_min_count_label, fasttext_args_set_minn,
+  fasttext_args_set_model, fasttext_args_set_neg, fasttext_args_set_output,
+  fasttext_args_set_pretrained_vectors, fasttext_args_set_qnorm, fasttext_args_set_qout,
+  fasttext_args_set_retrain, fasttext_args_set_save_output, fasttext_args_set_seed,
+  fasttext_args_set_t, fasttext_args_set_thread, fasttext_args_set_verbose,
+  fasttext_args_set_word_ngrams, fasttext_args_set_ws, fasttext_args_t,
+};
+use std::ffi::{c_char, CStr, CString};
+use std::fs::File;
+use std::io::{self, Read};
+
+use crate::api::error::FastTextError;
+use crate::api::fasttext::{handle_result, to_c_string};
+
+/// The loss function optimized during training.
+///
+/// The discriminants match `fasttext::loss_name` on the C++ side.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Loss {
+  /// Hierarchical softmax.
+  Hs = 1,
+  /// Negative sampling.
+  Ns = 2,
+  /// Full softmax.
+  Softmax = 3,
+  /// One-vs-all, i.e. independent binary classifiers per label.
+  Ova = 4,
+}
+
+impl Loss {
+  fn from_raw(value: i32) -> Option<Self> {
+    match value {
+      1 => Some(Loss::Hs),
+      2 => Some(Loss::Ns),
+      3 => Some(Loss::Softmax),
+      4 => Some(Loss::Ova),
+      _ => None,
+    }
+  }
+}
+
+/// The kind of model being trained.
+///
+/// The discriminants match `fasttext::model_name` on the C++ side.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ModelName {
+  /// Continuous bag of words word vectors.
+  Cbow = 1,
+  /// Skipgram word vectors.
+  Skipgram = 2,
+  /// Supervised text classifier.
+  Supervised = 3,
+}
+
+impl ModelName {
+  fn from_raw(value: i32) -> Option<Self> {
+    match value {
+      1 => Some(ModelName::Cbow),
+      2 => Some(ModelName::Skipgram),
+      3 => Some(ModelName::Supervised),
+      _ => None,
+    }
+  }
+}
+
+/// Hyperparameters used to train a model.
+///
+/// Every field but `deterministic` maps one-to-one onto a field of
+/// `fasttext::Args`. The `Default` values are the ones the `fasttext
+/// supervised` command uses, so a typical call only needs to set the input
+/// file:
+///
+/// ```ignore
+/// let args = TrainArgs { input: "train.txt".to_string(), epoch: 25, ..Default::default() };
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct TrainArgs {
+  /// Path to the training file.
+  pub input: String,
+  /// Learning rate.
+  pub lr: f64,
+  /// Change the rate of updates for the learning rate.
+  pub lr_update_rate: i32,
+  /// Size of the word vectors.
+  pub dim: i32,
+  /// Size of the context window.
+  pub ws: i32,
+  /// Number of epochs.
+  pub epoch: i32,
+  /// Minimal number of word occurrences.
+  pub min_count: i32,
+  /// Minimal number of label occurrences.
+  pub min_count_label: i32,
+  /// Number of negatives sampled.
+  pub neg: i32,
+  /// Max length of word n-gram.
+  pub word_ngrams: i32,
+  /// Loss function.
+  pub loss: Loss,
+  /// Number of buckets.
+  pub bucket: i32,
+  /// Min length of char n-gram.
+  pub minn: i32,
+  /// Max length of char n-gram.
+  pub maxn: i32,
+  /// Number of threads.
+  pub thread: i32,
+  /// Sampling threshold.
+  pub t: f64,
+  /// Labels prefix.
+  pub label: String,
+  /// Verbosity level.
+  pub verbose: i32,
+  /// Pretrained word vectors (`.vec` file) for supervised learning, empty for none.
+  pub pretrained_vectors: String,
+  /// Random generator seed.
+  pub seed: i32,
+  /// Train on a single thread so that the same inputs and seed always give the
+  /// same model, and save [`TrainArgs::training_hash`] with it.
+  pub deterministic: bool,
+}
+
+impl Default for TrainArgs {
+  fn default() -> Self {
+    TrainArgs {
+      input: String::new(),
+      lr: 0.1,
+      lr_update_rate: 100,
+      dim: 100,
+      ws: 5,
+      epoch: 5,
+      min_count: 1,
+      min_count_label: 0,
+      neg: 5,
+      word_ngrams: 1,
+      loss: Loss::Softmax,
+      bucket: 2000000,
+      minn: 0,
+      maxn: 0,
+      thread: 12,
+      t: 1e-4,
+      label: "__label__".to_string(),
+      verbose: 2,
+      pretrained_vectors: String::new(),
+      seed: 0,
+      deterministic: false,
+    }
+  }
+}
+
+impl TrainArgs {
+  /// The defaults of the `fasttext skipgram` and `fasttext cbow` commands.
+  ///
+  /// Compared to `Default`, these use a lower learning rate, ignore rare
+  /// words, learn character n-grams and optimize with negative sampling.
+  pub fn unsupervised() -> Self {
+    TrainArgs {
+      lr: 0.05,
+      min_count: 5,
+      minn: 3,
+      maxn: 6,
+      loss: Loss::Ns,
+      ..Default::default()
+    }
+  }
+
+  /// Sets [`TrainArgs::deterministic`].
+  ///
+  /// Hogwild updates from several threads race with each other, so a
+  /// deterministic training overrides `thread` with 1. The model then only
+  /// depends on the input, the pretrained vectors and the other arguments,
+  /// including `seed`.
+  pub fn deterministic(self, deterministic: bool) -> Self {
+    TrainArgs { deterministic, ..self }
+  }
+
+  /// Hashes what a deterministic training of `model` depends on, as 16 hex
+  /// digits.
+  ///
+  /// That is the content of `input` and `pretrained_vectors` rather than
+  /// their paths, and every argument but `verbose` and `thread`. Retraining
+  /// with the hash saved in a model gives the same model byte for byte. The
+  /// hash is FNV-1a, which is stable but not meant to resist tampering.
+  pub fn training_hash(&self, model: ModelName) -> Result<String, FastTextError> {
+    let mut hasher = Fnv1a::default();
+    // Every field is written explicitly and in a fixed order, so that the
+    // hash only changes when the scheme's version does.
+    hasher.write_str("fasttext-rs training v2");
+    hasher.write_i32(model as i32);
+    hasher.write_f64(self.lr);
+    hasher.write_i32(self.lr_update_rate);
+    hasher.write_i32(self.dim);
+    hasher.write_i32(self.ws);
+    hasher.write_i32(self.epoch);
+    hasher.write_i32(self.min_count);
+    hasher.write_i32(self.min_count_label);
+    hasher.write_i32(self.neg);
+    hasher.write_i32(self.word_ngrams);
+    hasher.write_i32(self.loss as i32);
+    hasher.write_i32(self.bucket);
+    hasher.write_i32(self.minn);
+    hasher.write_i32(self.maxn);
+    hasher.write_f64(self.t);
+    hasher.write_str(&self.label);
+    hasher.write_i32(self.seed);
+    for path in [&self.input, &self.pretrained_vectors] {
+      if !path.is_empty() {
+        hasher.write_file(path)?;
+      } else {
+        hasher.write(&0u64.to_le_bytes());
+      }
+    }
+    Ok(format!("{:016x}", hasher.0))
+  }
+
+  /// Builds the C++ `Args` for these hyperparameters and the given model kind.
+  ///
+  /// Useful with the training methods that take an [`Args`], such as
+  /// `FastText::train_with_progress`. A deterministic training still runs on
+  /// one thread through these, but only the methods taking `TrainArgs`, such
+  /// as `FastText::train_supervised`, record the training hash in the model.
+  pub fn to_args(&self, model: ModelName) -> Result<Args, FastTextError> {
+    // Same rule as `Args::parseArgs`: without word or char n-grams the
+    // buckets would never be used, so don't allocate them.
+    let bucket = if self.word_ngrams <= 1 && self.maxn == 0 { 0 } else { self.bucket };
+
+    Ok(
+      Args::new()
+        .with_input(&self.input)?
+        .with_lr(self.lr)
+        .with_lr_update_rate(self.lr_update_rate)
+        .with_dim(self.dim)
+        .with_ws(self.ws)
+        .with_epoch(self.epoch)
+        .with_min_count(self.min_count)
+        .with_min_count_label(self.min_count_label)
+        .with_neg(self.neg)
+        .with_word_ngrams(self.word_ngrams)
+        .with_loss(self.loss)
+        .with_model(model)
+        .with_bucket(bucket)
+        .with_minn(self.minn)
+        .with_maxn(self.maxn)
+        .with_thread(if self.deterministic { 1 } else { self.thread })
+        .with_t(self.t)
+        .with_label(&self.label)?
+        .with_verbose(self.verbose)
+        .with_pretrained_vectors(&self.pretrained_vectors)?
+        .with_seed(self.seed),
+    )
+  }
+}
+
+impl From<&Args> for TrainArgs {
+  fn from(args: &Args) -> Self {
+    TrainArgs {
+      input: args.input(),
+      lr: args.lr(),
+      lr_update_rate: args.lr_update_rate(),
+      dim: args.dim(),
+      ws: args.ws(),
+      epoch: args.epoch(),
+      min_count: args.min_count(),
+      min_count_label: args.min_count_label(),
+      neg: args.neg(),
+      word_ngrams: args.word_ngrams(),
+      loss: args.loss(),
+      bucket: args.bucket(),
+      minn: args.minn(),
+      maxn: args.maxn(),
+      thread: args.thread(),
+      t: args.t(),
+      label: args.label(),
+      verbose: args.verbose(),
+      pretrained_vectors: args.pretrained_vectors(),
+      seed: args.seed(),
+      deterministic: false,
+    }
+  }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is the same on every Rust release.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+  fn default() -> Self {
+    Fnv1a(0xcbf29ce484222325)
+  }
+}
+
+impl Fnv1a {
+  fn write(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+    }
+  }
+
+  fn write_i32(&mut self, value: i32) {
+    self.write(&value.to_le_bytes());
+  }
+
+  fn write_f64(&mut self, value: f64) {
+    self.write(&value.to_le_bytes());
+  }
+
+  /// Hashes the length of the string, then its bytes, so that consecutive
+  /// strings can't run into each other.
+  fn write_str(&mut self, value: &str) {
+    self.write(&(value.len() as u64).to_le_bytes());
+    self.write(value.as_bytes());
+  }
+
+  /// Hashes the length of the file, then its content.
+  fn write_file(&mut self, path: &str) -> Result<(), FastTextError> {
+    let io_error = |e: io::Error| FastTextError::Io {
+      path: Some(path.to_string()),
+      message: format!("Failed to read the training data: {}", e),
+    };
+    let mut file = File::open(path).map_err(io_error)?;
+    self.write(&file.metadata().map_err(io_error)?.len().to_le_bytes());
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+      match file.read(&mut buffer) {
+        Ok(0) => return Ok(()),
+        Ok(n) => self.write(&buffer[..n]),
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+        Err(e) => return Err(io_error(e)),
+      }
+    }
+  }
+}
+
+/// Hyperparameters for quantizing a supervised model, the typed equivalent of
+/// `fasttext quantize`.
+///
+/// The `Default` values are the ones of the command line tool. Setting a
+/// `cutoff` keeps only that many words and n-grams, and with `retrain` the
+/// remaining embeddings are finetuned on `input` for `epoch` epochs.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QuantizeArgs {
+  /// Path to the training file, only read when retraining.
+  pub input: String,
+  /// Number of words and n-grams to retain, 0 to keep them all.
+  pub cutoff: usize,
+  /// Whether embeddings are finetuned if a cutoff is applied.
+  pub retrain: bool,
+  /// Whether the norm is quantized separately.
+  pub qnorm: bool,
+  /// Whether the classifier is quantized too.
+  pub qout: bool,
+  /// Size of each sub-vector.
+  pub dsub: usize,
+  /// Number of epochs when retraining.
+  pub epoch: i32,
+  /// Learning rate when retraining.
+  pub lr: f64,
+  /// Number of threads when retraining.
+  pub thread: i32,
+  /// Verbosity level.
+  pub verbose: i32,
+}
+
+impl Default for QuantizeArgs {
+  fn default() -> Self {
+    QuantizeArgs {
+      input: String::new(),
+      cutoff: 0,
+      retrain: false,
+      qnorm: false,
+      qout: false,
+      dsub: 2,
+      epoch: 5,
+      lr: 0.05,
+      thread: 12,
+      verbose: 2,
+    }
+  }
+}
+
+impl QuantizeArgs {
+  /// Builds the C++ `Args` that `FastText::quantize` expects.
+  pub fn to_args(&self) -> Result<Args, FastTextError> {
+    if self.dsub == 0 {
+      return Err(invalid_argument("dsub must be greater than 0."));
+    }
+
+    Ok(
+      Args::new()
+        .with_input(&self.input)?
+        .with_cutoff(self.cutoff)
+        .with_retrain(self.retrain)
+        .with_qnorm(self.qnorm)
+        .with_qout(self.qout)
+        .with_dsub(self.dsub)
+        .with_epoch(self.epoch)
+        .with_lr(self.lr)
+        .with_thread(self.thread)
+        .with_verbose(self.verbose),
+    )
+  }
+}
+
+impl From<&Args> for QuantizeArgs {
+  fn from(args: &Args) -> Self {
+    QuantizeArgs {
+      input: args.input(),
+      cutoff: args.cutoff(),
+      retrain: args.retrain(),
+      qnorm: args.qnorm(),
+      qout: args.qout(),
+      dsub: args.dsub(),
+      epoch: args.epoch(),
+      lr: args.lr(),
+      thread: args.thread(),
+      verbose: args.verbose(),
+    }
+  }
+}
+
+/// The hyperparameters a model was trained with, as read back from the model.
+///
+/// Only part of `fasttext::Args` is saved in a model file. The label prefix
+/// isn't, so it can't be read back and is left out.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ModelArgs {
+  /// The kind of model.
+  pub model: ModelName,
+  /// Loss function.
+  pub loss: Loss,
+  /// Size of the word vectors.
+  pub dim: i32,
+  /// Size of the context window.
+  pub ws: i32,
+  /// Number of epochs.
+  pub epoch: i32,
+  /// Minimal number of word occurrences.
+  pub min_count: i32,
+  /// Number of negatives sampled.
+  pub neg: i32,
+  /// Max length of word n-gram.
+  pub word_ngrams: i32,
+  /// Number of buckets.
+  pub bucket: i32,
+  /// Min length of char n-gram.
+  pub minn: i32,
+  /// Max length of char n-gram.
+  pub maxn: i32,
+  /// Change the rate of updates for the learning rate.
+  pub lr_update_rate: i32,
+  /// Sampling threshold.
+  pub t: f64,
+  /// Whether the input matrix is quantized, as in `.ftz` models.
+  pub quantized: bool,
+  /// Whether the classifier is quantized as well.
+  pub qout: bool,
+}
+
+impl ModelArgs {
+  pub(crate) fn new(args: &Args, quantized: bool) -> Self {
+    ModelArgs {
+      model: args.model(),
+      loss: args.loss(),
+      dim: args.dim(),
+      ws: args.ws(),
+      epoch: args.epoch(),
+      min_count: args.min_count(),
+      neg: args.neg(),
+      word_ngrams: args.word_ngrams(),
+      bucket: args.bucket(),
+      minn: args.minn(),
+      maxn: args.maxn(),
+      lr_update_rate: args.lr_update_rate(),
+      t: args.t(),
+      quantized,
+      qout: args.qout(),
+    }
+  }
+}
+
+/// The kind of value a command line flag expects.
+#[derive(Clone, Copy)]
+enum FlagKind {
+  Str,
+  Int,
+  Float,
+  Loss,
+  Switch,
+}
+
+/// Every flag understood by `Args::parseArgs`.
+const CLI_FLAGS: &[(&str, FlagKind)] = &[
+  ("-input", FlagKind::Str),
+  ("-output", FlagKind::Str),
+  ("-lr", FlagKind::Float),
+  ("-lrUpdateRate", FlagKind::Int),
+  ("-dim", FlagKind::Int),
+  ("-ws", FlagKind::Int),
+  ("-epoch", FlagKind::Int),
+  ("-minCount", FlagKind::Int),
+  ("-minCountLabel", FlagKind::Int),
+  ("-neg", FlagKind::Int),
+  ("-wordNgrams", FlagKind::Int),
+  ("-loss", FlagKind::Loss),
+  ("-bucket", FlagKind::Int),
+  ("-minn", FlagKind::Int),
+  ("-maxn", FlagKind::Int),
+  ("-thread", FlagKind::Int),
+  ("-t", FlagKind::Float),
+  ("-label", FlagKind::Str),
+  ("-verbose", FlagKind::Int),
+  ("-pretrainedVectors", FlagKind::Str),
+  ("-saveOutput", FlagKind::Switch),
+  ("-seed", FlagKind::Int),
+  ("-qnorm", FlagKind::Switch),
+  ("-retrain", FlagKind::Switch),
+  ("-qout", FlagKind::Switch),
+  ("-cutoff", FlagKind::Int),
+  ("-dsub", FlagKind::Int),
+  ("-autotune-validation", FlagKind::Str),
+  ("-autotune-metric", FlagKind::Str),
+  ("-autotune-predictions", FlagKind::Int),
+  ("-autotune-duration", FlagKind::Int),
+  ("-autotune-modelsize", FlagKind::Str),
+];
+
+fn invalid_argument(message: &str) -> FastTextError {
+  FastTextError::InvalidArgument { message: message.to_string() }
+}
+
+/// Rejects everything `Args::parseArgs` would answer with `exit(EXIT_FAILURE)`,
+/// so that a bad flag can't take the whole process down.
+fn check_cli(argv: &[&str]) -> Result<(), FastTextError> {
+  if argv.is_empty() {
+    return Err(invalid_argument("Missing command, e.g. `supervised` or `skipgram`."));
+  }
+
+  let mut has_input = false;
+  let mut has_output = false;
+  let mut i = 1;
+  while i < argv.len() {
+    let flag = argv[i];
+    let kind = CLI_FLAGS
+      .iter()
+      .find(|(name, _)| *name == flag)
+      .map(|(_, kind)| *kind)
+      .ok_or_else(|| invalid_argument(&format!("Unknown argument: {}", flag)))?;
+    if let FlagKind::Switch = kind {
+      i += 1;
+      continue;
+    }
+
+    let value = *argv
+      .get(i + 1)
+      .ok_or_else(|| invalid_argument(&format!("{} is missing an argument", flag)))?;
+    let valid = match kind {
+      FlagKind::Str => true,
+      FlagKind::Int => value.parse::<i32>().is_ok(),
+      FlagKind::Float => parses_as_f32(value),
+      FlagKind::Loss => matches!(value, "hs" | "ns" | "softmax" | "one-vs-all" | "ova"),
+      FlagKind::Switch => unreachable!(),
+    };
+    if !valid {
+      return Err(invalid_argument(&format!("Invalid value for {}: {}", flag, value)));
+    }
+    has_input |= flag == "-input" && !value.is_empty();
+    has_output |= flag == "-output" && !value.is_empty();
+    i += 2;
+  }
+
+  if !has_input || !has_output {
+    return Err(invalid_argument("Empty input or output path."));
+  }
+  Ok(())
+}
+
+/// Whether `std::stof` reads `value` without going out of range, which it
+/// does for numbers that overflow `f32` or underflow to a subnormal.
+fn parses_as_f32(value: &str) -> bool {
+  match value.parse::<f64>() {
+    // `inf` and `nan` are spelled out, and in range.
+    Ok(_) if value.contains(['i', 'I', 'n', 'N']) => true,
+    Ok(parsed) => {
+      let mantissa = value.split(['e', 'E']).next().unwrap_or_default();
+      let zero = !mantissa.contains(|c: char| ('1'..='9').contains(&c));
+      zero || (f64::from(f32::MIN_POSITIVE)..=f64::from(f32::MAX)).contains(&parsed.abs())
+    }
+    Err(_) => false,
+  }
+}
+
+macro_rules! scalar_accessors {
+  ($($desc:literal: $name:ident, $with:ident, $ty:ty => $get:ident, $set:ident;)*) => {
+    $(
+      #[doc = concat!("Returns the ", $desc, ".")]
+      pub fn $name(&self) -> $ty {
+        unsafe { $get(self.handle) }
+      }
+
+      #[doc = concat!("Sets the ", $desc, ".")]
+      pub fn $with(self, value: $ty) -> Self {
+        unsafe { $set(self.handle, value) };
+        self
+      }
+    )*
+  };
+}
+
+macro_rules! switch_accessors {
+  ($($desc:literal: $name:ident, $with:ident => $get:ident, $set:ident;)*) => {
+    $(
+      #[doc = concat!("Returns whether to ", $desc, ".")]
+      pub fn $name(&self) -> bool {
+        unsafe { $get(self.handle) != 0 }
+      }
+
+      #[doc = concat!("Sets whether to ", $desc, ".")]
+      pub fn $with(self, value: bool) -> Self {
+        unsafe { $set(self.handle, value as i32) };
+        self
+      }
+    )*
+  };
+}
+
+macro_rules! string_accessors {
+  ($($desc:literal: $name:ident, $with:ident => $get:ident, $set:ident;)*) => {
+    $(
+      #[doc = concat!("Returns the ", $desc, ".")]
+      pub fn $name(&self) -> String {
+        unsafe { CStr::from_ptr($get(self.handle)).to_string_lossy().into_owned() }
+      }
+
+      #[doc = concat!("Sets the ", $desc, ".")]
+      pub fn $with(self, value: &str) -> Result<Self, FastTextError> {
+        let c_value = to_c_string(stringify!($name), value)?;
+        // The setter copies the string into the C++ object.
+        unsafe { $set(self.handle, c_value.as_ptr()) };
+        Ok(self)
+      }
+    )*
+  };
+}
+
+/// A safe Rust wrapper for `fasttext::Args`.
+///
+/// Owns the underlying `fasttext_args_t` and deletes it on drop. Setters are
+/// builder style and consume `self`, so a full set of arguments can be built
+/// in a single expression:
+///
+/// ```ignore
+/// let args = Args::new()
+///   .with_input("train.txt")?
+///   .with_model(ModelName::Supervised)
+///   .with_loss(Loss::Ova)
+///   .with_epoch(25);
+/// ```
+pub struct Args {
+  handle: *mut fasttext_args_t,
+}
+
+impl Args {
+  /// Creates a new set of arguments holding the `fasttext::Args` defaults.
+  ///
+  /// These are the skipgram defaults; use [`Args::from_cli`] or the setters
+  /// to get the defaults of another command.
+  pub fn new() -> Self {
+    Args { handle: unsafe { fasttext_args_new() } }
+  }
+
+  /// Parses arguments the same way the `fasttext` command line tool does.
+  ///
+  /// `argv` starts with the command followed by its flags, e.g.
+  /// `["supervised", "-input", "train.txt", "-output", "model", "-epoch", "25"]`.
+  /// Like the command line tool, `-input` and `-output` are mandatory.
+  ///
+  /// # Arguments
+  ///
+  /// * `argv` - The command and flags, without the program name.
+  pub fn from_cli(argv: &[&str]) -> Result<Self, FastTextError> {
+    check_cli(argv)?;
+
+    let c_argv = std::iter::once("fasttext")
+      .chain(argv.iter().copied())
+      .map(|arg| to_c_string("argument", arg))
+      .collect::<Result<Vec<_>, _>>()?;
+    // `parseArgs` copies every argument into a `std::string`, it never writes through these.
+    let mut c_argv_ptrs: Vec<*mut c_char> =
+      c_argv.iter().map(|arg| arg.as_ptr() as *mut c_char).collect();
+
+    let args = Args::new();
+    let result = unsafe {
+      fasttext_args_parse(args.handle, c_argv_ptrs.len() as i32, c_argv_ptrs.as_mut_ptr())
+    };
+    handle_result(result)?;
+    Ok(args)
+  }
+
+  /// Takes ownership of a handle allocated by the C API.
+  ///
+  /// # Safety
+  ///
+  /// `handle` must be a valid `fasttext_args_t` that nothing else deletes.
+  pub(crate) unsafe fn from_raw(handle: *mut fasttext_args_t) -> Self {
+    Args { handle }
+  }
+
+  pub(crate) fn handle(&self) -> *mut fasttext_args_t {
+    self.handle
+  }
+
+  /// Returns whether an argument was set explicitly, e.g. on the command line.
+  ///
+  /// Autotune only optimizes the hyperparameters that aren't.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the flag without its dash, e.g. `"epoch"` or `"wordNgrams"`.
+  pub fn is_manual(&self, name: &str) -> bool {
+    match CString::new(name) {
+      Ok(c_name) => unsafe { fasttext_args_is_manual(self.handle, c_name.as_ptr()) },
+      Err(_) => false,
+    }
+  }
+
+  /// Marks an argument as set explicitly, see [`Args::is_manual`].
+  pub fn with_manual(self, name: &str) -> Result<Self, FastTextError> {
+    let c_name = to_c_string("name", name)?;
+    unsafe { fasttext_args_set_manual(self.handle, c_name.as_ptr()) };
+    Ok(self)
+  }
+
+  /// Returns the loss function.
+  pub fn loss(&self) -> Loss {
+    let loss = unsafe { fasttext_args_get_loss(self.handle) };
+    Loss::from_raw(loss).expect("fasttext::Args holds an unknown loss_name")
+  }
+
+  /// Sets the loss function.
+  pub fn with_loss(self, loss: Loss) -> Self {
+    unsafe { fasttext_args_set_loss(self.handle, loss as i32) };
+    self
+  }
+
+  /// Returns the kind of model.
+  pub fn model(&self) -> ModelName {
+    let model = unsafe { fasttext_args_get_model(self.handle) };
+    ModelName::from_raw(model).expect("fasttext::Args holds an unknown model_name")
+  }
+
+  /// Sets the kind of model.
+  pub fn with_model(self, model: ModelName) -> Self {
+    unsafe { fasttext_args_set_model(self.handle, model as i32) };
+    self
+  }
+
+  string_accessors! {
+    "training file path": input, with_input => fasttext_args_get_input, fasttext_args_set_input;
+    "output file path": output, with_output => fasttext_args_get_output, fasttext_args_set_output;
+    "labels prefix": label, with_label => fasttext_args_get_label, fasttext_args_set_label;
+    "pretrained word vectors path": pretrained_vectors, with_pretrained_vectors
+      => fasttext_args_get_pretrained_vectors, fasttext_args_set_pretrained_vectors;
+    "autotune validation file path": autotune_validation_file, with_autotune_validation_file
+      => fasttext_args_get_autotune_validation_file, fasttext_args_set_autotune_validation_file;
+    "autotune metric objective": autotune_metric, with_autotune_metric
+      => fasttext_args_get_autotune_metric, fasttext_args_set_autotune_metric;
+    "autotune model size constraint": autotune_model_size, with_autotune_model_size
+      => fasttext_args_get_autotune_model_size, fasttext_args_set_autotune_model_size;
+  }
+
+  scalar_accessors! {
+    "learning rate": lr, with_lr, f64 => fasttext_args_get_lr, fasttext_args_set_lr;
+    "rate of updates for the learning rate": lr_update_rate, with_lr_update_rate, i32
+      => fasttext_args_get_lr_update_rate, fasttext_args_set_lr_update_rate;
+    "size of word vectors": dim, with_dim, i32 => fasttext_args_get_dim, fasttext_args_set_dim;
+    "size of the context window": ws, with_ws, i32 => fasttext_args_get_ws, fasttext_args_set_ws;
+    "number of epochs": epoch, with_epoch, i32
+      => fasttext_args_get_epoch, fasttext_args_set_epoch;
+    "minimal number of word occurrences": min_count, with_min_count, i32
+      => fasttext_args_get_min_count, fasttext_args_set_min_count;
+    "minimal number of label occurrences": min_count_label, with_min_count_label, i32
+      => fasttext_args_get_min_count_label, fasttext_args_set_min_count_label;
+    "number of negatives sampled": neg, with_neg, i32
+      => fasttext_args_get_neg, fasttext_args_set_neg;
+    "max length of word n-gram": word_ngrams, with_word_ngrams, i32
+      => fasttext_args_get_word_ngrams, fasttext_args_set_word_ngrams;
+    "number of buckets": bucket, with_bucket, i32
+      => fasttext_args_get_bucket, fasttext_args_set_bucket;
+    "min length of char n-gram": minn, with_minn, i32
+      => fasttext_args_get_minn, fasttext_args_set_minn;
+    "max length of char n-gram": maxn, with_maxn, i32
+      => fasttext_args_get_maxn, fasttext_args_set_maxn;
+    "number of threads": thread, with_thread, i32
+      => fasttext_args_get_thread, fasttext_args_set_thread;
+    "sampling threshold": t, with_t, f64 => fasttext_args_get_t, fasttext_args_set_t;
+    "verbosity level": verbose, with_verbose, i32
+      => fasttext_args_get_verbose, fasttext_args_set_verbose;
+    "random generator seed": seed, with_seed, i32
+      => fasttext_args_get_seed, fasttext_args_set_seed;
+    "number of words and ngrams to retain when quantizing": cutoff, with_cutoff, usize
+      => fasttext_args_get_cutoff, fasttext_args_set_cutoff;
+    "size of each sub-vector when quantizing": dsub, with_dsub, usize
+      => fasttext_args_get_dsub, fasttext_args_set_dsub;
+    "number of predictions used for autotune evaluation": autotune_predictions,
+      with_autotune_predictions, i32
+      => fasttext_args_get_autotune_predictions, fasttext_args_set_autotune_predictions;
+    "maximum duration of autotune in seconds": autotune_duration, with_autotune_duration, i32
+      => fasttext_args_get_autotune_duration, fasttext_args_set_autotune_duration;
+  }
+
+  switch_accessors! {
+    "save the output parameters": save_output, with_save_output
+      => fasttext_args_get_save_output, fasttext_args_set_save_output;
+    "quantize the classifier when quantizing": qout, with_qout
+      => fasttext_args_get_qout, fasttext_args_set_qout;
+    "finetune the embeddings when a cutoff is applied": retrain, with_retrain
+      => fasttext_args_get_retrain, fasttext_args_set_retrain;
+    "quantize the norm separately": qnorm, with_qnorm
+      => fasttext_args_get_qnorm, fasttext_args_set_qnorm;
+  }
+}
+
+impl std::fmt::Debug for Args {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Args")
+      .field("input", &self.input())
+      .field("output", &self.output())
+      .field("model", &self.model())
+      .field("loss", &self.loss())
+      .field("lr", &self.lr())
+      .field("lr_update_rate", &self.lr_update_rate())
+      .field("dim", &self.dim())
+      .field("ws", &self.ws())
+      .field("epoch", &self.epoch())
+      .field("min_count", &self.min_count())
+      .field("min_count_label", &self.min_count_label())
+      .field("neg", &self.neg())
+      .field("word_ngrams", &self.word_ngrams())
+      .field("bucket", &self.bucket())
+      .field("minn", &self.minn())
+      .field("maxn", &self.maxn())
+      .field("thread", &self.thread())
+      .field("t", &self.t())
+      .field("label", &self.label())
+      .field("verbose", &self.verbose())
+      .field("pretrained_vectors", &self.pretrained_vectors())
+      .field("save_output", &self.save_output())
+      .field("seed", &self.seed())
+      .field("qout", &self.qout())
+      .field("retrain", &self.retrain())
+      .field("qnorm", &self.qnorm())
+      .field("cutoff", &self.cutoff())
+      .field("dsub", &self.dsub())
+      .field("autotune_validation_file", &self.autotune_validation_file())
+      .field("autotune_metric", &self.autotune_metric())
+      .field("autotune_predictions", &self.autotune_predictions())
+      .field("autotune_duration", &self.autotune_duration())
+      .field("autotune_model_size", &self.autotune_model_size())
+      .finish()
+  }
+}
+
+impl Default for Args {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Drop for Args {
+  fn drop(&mut self) {
+    if !self.handle.is_null() {
+      unsafe {
+        fasttext_args_delete(self.handle);
+      }
+    }
+  }
+}
+
+// `Args` is a plain value object on the C++ side and only mutated through `self`.
+unsafe impl Send for Args {}
+unsafe impl Sync for Args {}
@@ -0,0 +1,224 @@
+use fasttext_bindings::bindings::fasttext_args_t;
+use std::any::Any;
+use std::ffi::c_void;
+use std::mem::ManuallyDrop;
+use std::ops::ControlFlow;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::api::args::{Args, ModelName, QuantizeArgs, TrainArgs};
+use crate::api::error::FastTextError;
+use crate::api::train::AbortHandle;
+
+/// The objective autotune maximizes on the validation file.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AutotuneMetric {
+  /// F1-score over every label.
+  F1,
+  /// F1-score of a single label, including its prefix.
+  F1Label(String),
+  /// Best precision reached with a recall of at least `recall`, between 0 and 1,
+  /// over every label or for a single one.
+  PrecisionAtRecall { recall: f64, label: Option<String> },
+  /// Best recall reached with a precision of at least `precision`, between 0
+  /// and 1, over every label or for a single one.
+  RecallAtPrecision { precision: f64, label: Option<String> },
+}
+
+impl AutotuneMetric {
+  /// The value of the `-autotune-metric` flag for this metric.
+  fn to_cli(&self) -> String {
+    // fastText takes a percentage, which is rounded since e.g. 0.3 * 100.0
+    // is 30.000000000000004.
+    let with_label = |name: &str, value: f64, label: &Option<String>| {
+      let percent = format!("{:.6}", value * 100.0);
+      let percent = percent.trim_end_matches('0').trim_end_matches('.');
+      match label {
+        Some(label) => format!("{}:{}:{}", name, percent, label),
+        None => format!("{}:{}", name, percent),
+      }
+    };
+    match self {
+      AutotuneMetric::F1 => "f1".to_string(),
+      AutotuneMetric::F1Label(label) => format!("f1:{}", label),
+      AutotuneMetric::PrecisionAtRecall { recall, label } => {
+        with_label("precisionAtRecall", *recall, label)
+      }
+      AutotuneMetric::RecallAtPrecision { precision, label } => {
+        with_label("recallAtPrecision", *precision, label)
+      }
+    }
+  }
+}
+
+/// A hyperparameter autotune can optimize.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Hyperparameter {
+  Epoch,
+  Lr,
+  Dim,
+  WordNgrams,
+  Loss,
+  Bucket,
+  Minn,
+  Maxn,
+  /// Size of the sub-vectors, only searched when quantizing.
+  Dsub,
+}
+
+impl Hyperparameter {
+  /// The name of the command line flag, which is how `fasttext::Args` tracks it.
+  fn flag(self) -> &'static str {
+    match self {
+      Hyperparameter::Epoch => "epoch",
+      Hyperparameter::Lr => "lr",
+      Hyperparameter::Dim => "dim",
+      Hyperparameter::WordNgrams => "wordNgrams",
+      Hyperparameter::Loss => "loss",
+      Hyperparameter::Bucket => "bucket",
+      Hyperparameter::Minn => "minn",
+      Hyperparameter::Maxn => "maxn",
+      Hyperparameter::Dsub => "dsub",
+    }
+  }
+}
+
+/// Settings of a hyperparameter search, the typed equivalent of the
+/// `-autotune-*` flags of `fasttext supervised`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AutotuneConfig {
+  /// Path to the labelled file every trial is evaluated on.
+  pub validation_file: String,
+  /// The metric to maximize.
+  pub metric: AutotuneMetric,
+  /// Number of labels predicted for each validation example.
+  pub predictions: i32,
+  /// Maximum duration of the search in seconds. The model is trained once
+  /// more with the best hyperparameters after that.
+  pub duration: i32,
+  /// Maximum size in bytes of the final model. When set, every trial is
+  /// quantized and the size of the sub-vectors is searched too.
+  pub model_size: Option<u64>,
+  /// The first hyperparameters tried, and the value of the ones in `fixed`.
+  /// The input is ignored in favour of the one given to `FastText::autotune`.
+  pub args: TrainArgs,
+  /// Hyperparameters that keep their value from `args` instead of being searched.
+  pub fixed: Vec<Hyperparameter>,
+}
+
+impl Default for AutotuneConfig {
+  fn default() -> Self {
+    AutotuneConfig {
+      validation_file: String::new(),
+      metric: AutotuneMetric::F1,
+      predictions: 1,
+      duration: 300,
+      model_size: None,
+      args: TrainArgs::default(),
+      fixed: Vec::new(),
+    }
+  }
+}
+
+impl AutotuneConfig {
+  /// Builds the C++ `Args` for a search on `input`.
+  pub fn to_args(&self, input: &str) -> Result<Args, FastTextError> {
+    let mut args = self
+      .args
+      .to_args(ModelName::Supervised)?
+      .with_input(input)?
+      // Unlike plain training, autotune may pick word or char n-grams later.
+      .with_bucket(self.args.bucket)
+      .with_autotune_validation_file(&self.validation_file)?
+      .with_autotune_metric(&self.metric.to_cli())?
+      .with_autotune_predictions(self.predictions)
+      .with_autotune_duration(self.duration)
+      .with_autotune_model_size(&self.model_size.map(|size| size.to_string()).unwrap_or_default())?;
+    for hyperparameter in &self.fixed {
+      args = args.with_manual(hyperparameter.flag())?;
+    }
+    Ok(args)
+  }
+}
+
+/// The outcome of a single autotune trial.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AutotuneTrial {
+  /// The number of the trial, starting at 1.
+  pub trial: i32,
+  /// The hyperparameters the model was trained with.
+  pub args: TrainArgs,
+  /// How the model was quantized, when a model size was requested.
+  pub quantize: Option<QuantizeArgs>,
+  /// The value of the metric on the validation file, `NaN` if the training
+  /// diverged or the model didn't fit in the requested size.
+  pub score: f64,
+}
+
+/// The outcome of a whole autotune search.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AutotuneResult {
+  /// The best hyperparameters, which the model was trained with.
+  pub args: TrainArgs,
+  /// How the model was quantized, when a model size was requested.
+  pub quantize: Option<QuantizeArgs>,
+  /// The value of the metric on the validation file with these hyperparameters.
+  pub score: f64,
+  /// Number of trials run.
+  pub trials: i32,
+}
+
+/// Everything the C++ autotune needs to report trials to Rust.
+pub(crate) struct AutotuneState<F> {
+  callback: F,
+  abort: AbortHandle,
+  quantized: bool,
+  panic: Option<Box<dyn Any + Send>>,
+}
+
+impl<F> AutotuneState<F>
+where
+  F: FnMut(&AutotuneTrial) -> ControlFlow<()>,
+{
+  pub(crate) fn new(callback: F, abort: AbortHandle, quantized: bool) -> Self {
+    AutotuneState { callback, abort, quantized, panic: None }
+  }
+
+  /// Takes the payload of a panic raised by the callback, if any.
+  pub(crate) fn take_panic(&mut self) -> Option<Box<dyn Any + Send>> {
+    self.panic.take()
+  }
+
+  /// The `fasttext_autotune_callback_t` handed to the C API along with `self` as user data.
+  ///
+  /// Autotune runs its trials one after the other on the calling thread.
+  /// Panics are caught here, since unwinding into C++ is not allowed, and
+  /// turned into a stop.
+  pub(crate) unsafe extern "C" fn trampoline(
+    trial: i32,
+    args: *const fasttext_args_t,
+    score: f64,
+    user_data: *mut c_void,
+  ) -> bool {
+    let state = unsafe { &mut *(user_data as *mut Self) };
+    if state.abort.is_aborted() {
+      return false;
+    }
+
+    // The arguments belong to the autotune, they must not be deleted here.
+    let args = ManuallyDrop::new(unsafe { Args::from_raw(args as *mut fasttext_args_t) });
+    let trial = AutotuneTrial {
+      trial,
+      args: TrainArgs::from(&*args),
+      quantize: state.quantized.then(|| QuantizeArgs::from(&*args)),
+      score,
+    };
+    match catch_unwind(AssertUnwindSafe(|| (state.callback)(&trial))) {
+      Ok(ControlFlow::Continue(())) => true,
+      Ok(ControlFlow::Break(())) => false,
+      Err(payload) => {
+        state.panic = Some(payload);
+        false
+      }
+    }
+  }
+}
@@ -0,0 +1,92 @@
+#[cfg(feature = "cpp")]
+use fasttext_bindings::bindings::{
+  FASTTEXT_ERROR_INVALID_ARGUMENT, FASTTEXT_ERROR_INVALID_MODEL_FORMAT, FASTTEXT_ERROR_IO,
+  FASTTEXT_ERROR_MODEL_NOT_LOADED, FASTTEXT_ERROR_NOT_MAPPABLE, FASTTEXT_ERROR_NOT_SUPERVISED,
+  FASTTEXT_ERROR_QUANTIZED_UNSUPPORTED,
+};
+use std::fmt;
+
+/// The ways a call into fastText can fail.
+///
+/// The C API reports an error code next to its message, so most variants can
+/// be matched on without looking at the message at all.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FastTextError {
+  /// The file or buffer isn't a fastText model, or was written by an
+  /// incompatible version of fastText.
+  InvalidModelFormat { message: String },
+  /// A file couldn't be opened, read or written. `path` is `None` when the
+  /// data came from a reader rather than a file.
+  Io { path: Option<String>, message: String },
+  /// A string argument contains a NUL byte, which can't be passed to C++.
+  NulInInput { arg: String },
+  /// The instance holds no model yet, load or train one first.
+  ModelNotLoaded,
+  /// The operation needs a supervised model.
+  NotSupervised,
+  /// The operation isn't supported by quantized models.
+  QuantizedUnsupported,
+  /// The arguments were rejected, before reaching fastText or by the C API.
+  InvalidArgument { message: String },
+  /// The model file can't be memory-mapped, e.g. because it's quantized or
+  /// the platform has no mappings. It can still be loaded normally.
+  NotMappable { message: String },
+  /// The training was stopped through an `AbortHandle` or its callback.
+  Aborted,
+  /// Any other exception thrown by fastText.
+  Cpp { message: String },
+}
+
+impl FastTextError {
+  /// Maps an error code of the C API and its message to a variant.
+  #[cfg(feature = "cpp")]
+  pub(crate) fn from_code(code: u32, message: String) -> Self {
+    match code {
+      FASTTEXT_ERROR_INVALID_MODEL_FORMAT => FastTextError::InvalidModelFormat { message },
+      FASTTEXT_ERROR_IO => FastTextError::Io { path: None, message },
+      FASTTEXT_ERROR_MODEL_NOT_LOADED => FastTextError::ModelNotLoaded,
+      FASTTEXT_ERROR_NOT_SUPERVISED => FastTextError::NotSupervised,
+      FASTTEXT_ERROR_QUANTIZED_UNSUPPORTED => FastTextError::QuantizedUnsupported,
+      FASTTEXT_ERROR_INVALID_ARGUMENT => FastTextError::InvalidArgument { message },
+      FASTTEXT_ERROR_NOT_MAPPABLE => FastTextError::NotMappable { message },
+      _ => FastTextError::Cpp { message },
+    }
+  }
+
+  /// Attaches the path of the file involved to an `Io` error.
+  pub(crate) fn at_path(self, path: &str) -> Self {
+    match self {
+      FastTextError::Io { path: None, message } => {
+        FastTextError::Io { path: Some(path.to_string()), message }
+      }
+      e => e,
+    }
+  }
+}
+
+impl fmt::Display for FastTextError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      FastTextError::InvalidModelFormat { message }
+      | FastTextError::Io { message, .. }
+      | FastTextError::InvalidArgument { message }
+      | FastTextError::NotMappable { message }
+      | FastTextError::Cpp { message } => write!(f, "{}", message),
+      FastTextError::NulInInput { arg } => write!(f, "{} contains a NUL byte.", arg),
+      FastTextError::ModelNotLoaded => write!(f, "No model was loaded or trained."),
+      FastTextError::NotSupervised => write!(f, "The model needs to be supervised."),
+      FastTextError::QuantizedUnsupported => write!(f, "Not supported by quantized models."),
+      FastTextError::Aborted => write!(f, "Training was aborted."),
+    }
+  }
+}
+
+impl std::error::Error for FastTextError {}
+
+// The checked-in bridge glue still passes errors to Dart as strings, until it
+// is regenerated with `flutter_rust_bridge_codegen generate`.
+impl From<FastTextError> for String {
+  fn from(e: FastTextError) -> Self {
+    e.to_string()
+  }
+}
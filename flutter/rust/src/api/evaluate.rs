@@ -0,0 +1,111 @@
+use fasttext_bindings::bindings::{
+  fasttext_free_score_vs_true, fasttext_meter_f1_score, fasttext_meter_label,
+  fasttext_meter_label_f1_score, fasttext_meter_label_precision, fasttext_meter_label_recall,
+  fasttext_meter_nexamples, fasttext_meter_nlabels, fasttext_meter_precision,
+  fasttext_meter_recall, fasttext_meter_score_vs_true, fasttext_meter_t,
+};
+use std::ffi::CStr;
+
+/// The metrics of a supervised model on a labelled test set, as computed by
+/// `fasttext test` and `fasttext test-label`.
+///
+/// Undefined ratios, such as the precision of a label that was never
+/// predicted, are `NaN` like on the C++ side.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EvaluationReport {
+  /// Number of examples, i.e. lines with at least one label and one word.
+  pub nexamples: u64,
+  /// Number of labels predicted for each example.
+  pub k: i32,
+  /// Minimal probability of a predicted label.
+  pub threshold: f32,
+  /// Precision at `k` over every example.
+  pub precision: f64,
+  /// Recall at `k` over every example.
+  pub recall: f64,
+  /// F1-score at `k` over every example.
+  pub f1_score: f64,
+  /// The metrics of every label in the model, in the order of the dictionary.
+  pub labels: Vec<LabelMetrics>,
+}
+
+/// The metrics of a single label.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LabelMetrics {
+  /// The label, including its prefix.
+  pub label: String,
+  /// Number of examples having this label.
+  pub gold: u64,
+  /// Number of examples this label was predicted for.
+  pub predicted: u64,
+  /// Share of the predictions of this label that were correct.
+  pub precision: f64,
+  /// Share of the examples having this label it was predicted for.
+  pub recall: f64,
+  /// Harmonic mean of the precision and the recall.
+  pub f1_score: f64,
+  /// The probability of every prediction of this label and whether it was
+  /// correct, sorted by probability. Examples where the label was missed
+  /// appear with a probability of `-1.0`, so that the recall of a
+  /// precision-recall curve built from these pairs is exact.
+  pub score_vs_true: Vec<(f32, bool)>,
+}
+
+impl EvaluationReport {
+  /// Returns the metrics of the given label, if the model knows it.
+  pub fn label(&self, label: &str) -> Option<&LabelMetrics> {
+    self.labels.iter().find(|metrics| metrics.label == label)
+  }
+
+  /// Builds the report from a meter returned by the C API, without freeing it.
+  ///
+  /// # Safety
+  ///
+  /// `meter` must be a valid, non-null meter.
+  pub(crate) unsafe fn from_meter(meter: *mut fasttext_meter_t, k: i32, threshold: f32) -> Self {
+    let nlabels = unsafe { fasttext_meter_nlabels(meter) };
+    let labels = (0..nlabels).map(|id| unsafe { LabelMetrics::from_meter(meter, id) }).collect();
+
+    unsafe {
+      EvaluationReport {
+        nexamples: fasttext_meter_nexamples(meter),
+        k,
+        threshold,
+        precision: fasttext_meter_precision(meter),
+        recall: fasttext_meter_recall(meter),
+        f1_score: fasttext_meter_f1_score(meter),
+        labels,
+      }
+    }
+  }
+}
+
+impl LabelMetrics {
+  /// # Safety
+  ///
+  /// `meter` must be a valid, non-null meter and `id` one of its labels.
+  unsafe fn from_meter(meter: *mut fasttext_meter_t, id: i32) -> Self {
+    let mut n_scores: usize = 0;
+    let score_vs_true = unsafe {
+      let scores_ptr = fasttext_meter_score_vs_true(meter, id, &mut n_scores);
+      let scores = std::slice::from_raw_parts(scores_ptr, n_scores)
+        .iter()
+        .map(|s| (s.score, s.gold == 1.0))
+        .collect::<Vec<_>>();
+      fasttext_free_score_vs_true(scores_ptr);
+      scores
+    };
+
+    unsafe {
+      LabelMetrics {
+        label: CStr::from_ptr(fasttext_meter_label(meter, id)).to_string_lossy().into_owned(),
+        gold: score_vs_true.iter().filter(|(_, gold)| *gold).count() as u64,
+        predicted: score_vs_true.iter().filter(|(score, _)| *score >= 0.0).count() as u64,
+        precision: fasttext_meter_label_precision(meter, id),
+        recall: fasttext_meter_label_recall(meter, id),
+        f1_score: fasttext_meter_label_f1_score(meter, id),
+        score_vs_true,
+      }
+    }
+  }
+}
@@ -2,21 +2,57 @@ use fasttext_bindings::bindings::{
   fasttext_delete, fasttext_free_predictions, fasttext_load_model, fasttext_new, fasttext_predict,
   fasttext_load_model_from_buffer, fasttext_t, fasttext_get_nn, fasttext_free_float_char_pair,
   fasttext_get_analogies, fasttext_get_word_id, fasttext_get_subword_id, fasttext_save_model,
-  fasttext_get_dimension, fasttext_get_word_vector, fasttext_get_sentence_vector, HasError
+  fasttext_get_dimension, fasttext_get_word_vector, fasttext_get_sentence_vector,
+  fasttext_train_with_callback, fasttext_quantize, fasttext_get_model_size, fasttext_test,
+  fasttext_test_from_reader, fasttext_meter_delete, fasttext_autotune, fasttext_get_args,
+  fasttext_free_error, fasttext_predict_batch, fasttext_free_predictions_batch,
+  fasttext_prediction_t, fasttext_get_nwords, fasttext_get_nlabels, fasttext_get_ntokens,
+  fasttext_get_words, fasttext_get_labels, fasttext_free_tokens, fasttext_is_quant,
+  fasttext_get_subwords, fasttext_free_subwords, fasttext_get_ngram_vectors,
+  fasttext_free_ngram_vectors, fasttext_explain, fasttext_free_token_contributions,
+  FASTTEXT_NGRAM_KIND_WORD_NGRAM, FASTTEXT_NGRAM_KIND_EOS, fasttext_get_input_matrix,
+  fasttext_get_output_matrix,
+  fasttext_save_output, fasttext_save_model_to_writer, fasttext_load_model_from_reader,
+  fasttext_load_model_mmap, fasttext_get_metadata, fasttext_set_metadata, fasttext_free_string,
+  TokensResult, HasError
 };
-use std::ffi::{c_void, CStr, CString};
-use flutter_rust_bridge::frb;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::ops::ControlFlow;
+use flutter_rust_bridge::{frb, DartFnFuture};
+use futures::executor::block_on;
 
-fn handle_result<T: HasError>(result: T) -> Result<T::ResultType, String> {
+use crate::api::args::{Args, Loss, ModelArgs, ModelName, QuantizeArgs, TrainArgs};
+use crate::api::autotune::{AutotuneConfig, AutotuneResult, AutotuneState, AutotuneTrial};
+use crate::api::error::FastTextError;
+use crate::api::evaluate::EvaluationReport;
+use crate::api::io::{ReadState, WriteState};
+use crate::api::matrix::MatrixView;
+pub use crate::api::prediction::Prediction;
+use crate::api::vectors::{VectorFormat, VectorWriter};
+use crate::api::train::{AbortHandle, ProgressState, TrainProgress, TrainStatus};
+
+/// The metadata key of [`TrainArgs::training_hash`].
+const TRAINING_HASH_KEY: &str = "training_hash";
+
+pub(crate) fn handle_result<T: HasError>(result: T) -> Result<T::ResultType, FastTextError> {
   let error = result.error();
   if error.is_null() {
     Ok(result.result())
   } else {
-    let err = unsafe { CStr::from_ptr(error).to_string_lossy().into_owned() };
-    Err(err)
+    // The message is a copy owned by the caller, free it once read.
+    let message = unsafe { CStr::from_ptr(error).to_string_lossy().into_owned() };
+    unsafe { fasttext_free_error(error) };
+    Err(FastTextError::from_code(result.error_code(), message))
   }
 }
 
+/// Converts a string argument for the C API, `arg` names it in the error.
+pub(crate) fn to_c_string(arg: &str, value: &str) -> Result<CString, FastTextError> {
+  CString::new(value).map_err(|_| FastTextError::NulInInput { arg: arg.to_string() })
+}
+
 /// A safe Rust wrapper for a fastText model.
 ///
 /// This struct handles the creation and destruction of the underlying
@@ -26,27 +62,43 @@ fn handle_result<T: HasError>(result: T) -> Result<T::ResultType, String> {
 pub struct FastText {
   // This is the opaque pointer to the C++ fastText object.
   handle: *mut fasttext_t,
+  // Shared with every `AbortHandle` given out for this instance.
+  abort: AbortHandle,
+}
+
+/// What the token of a [`TokenContribution`] is.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NgramKind {
+  /// A single word, including its character n-grams.
+  Word,
+  /// A sequence of words, up to `word_ngrams` long, separated by a space.
+  WordNgram,
+  /// The end of the line, which fastText reads as the `</s>` word when the
+  /// text has a newline.
+  EndOfLine,
 }
 
-/// Represents a single prediction from the fastText model.
+/// How much a token of the input moved the score of a label.
 #[derive(Debug, PartialEq, Clone)]
-pub struct Prediction {
-  pub probability: f32,
-  pub label: String,
+pub struct TokenContribution {
+  pub token: String,
+  pub ngram_kind: NgramKind,
+  /// The share of the label's logit. The scores of a text add up to the logit.
+  pub score: f32,
 }
 
 impl FastText {
   /// Creates a new fastText instance.
   #[frb(sync)]
-  pub fn new() -> Result<Self, String> {
+  pub fn new() -> Result<Self, FastTextError> {
     // unsafe block is necessary to call C functions.
     let result = unsafe { fasttext_new() };
     let handle = handle_result(result)?;
 
     if handle.is_null() {
-      Err("Failed to create fastText handle.".to_string())
+      Err(FastTextError::Cpp { message: "Failed to create fastText handle.".to_string() })
     } else {
-      Ok(FastText { handle })
+      Ok(FastText { handle, abort: AbortHandle::default() })
     }
   }
 
@@ -55,14 +107,13 @@ impl FastText {
   /// # Arguments
   ///
   /// * `path` - The file path of the model to load.
-  pub fn load_model(&mut self, path: &str) -> Result<(), String> {
-    let c_path = CString::new(path)
-      .map_err(|e| format!("Failed to create CString from path: {}", e))?;
+  pub fn load_model(&mut self, path: &str) -> Result<(), FastTextError> {
+    let c_path = to_c_string("path", path)?;
 
     // This is safe because we've checked the handle is not null on creation,
     // and the CString is valid.
     let result = unsafe { fasttext_load_model(self.handle, c_path.as_ptr()) };
-    handle_result(result)?;
+    handle_result(result).map_err(|e| e.at_path(path))?;
 
     Ok(())
   }
@@ -72,7 +123,7 @@ impl FastText {
   /// # Arguments
   ///
   /// * `buffer` - A byte slice containing the model data.
-  pub fn load_model_from_buffer(&mut self, buffer: &[u8]) -> Result<(), String> {
+  pub fn load_model_from_buffer(&mut self, buffer: &[u8]) -> Result<(), FastTextError> {
     // This is safe because we've checked the handle is not null on creation.
     let result = unsafe {
       fasttext_load_model_from_buffer(self.handle, buffer.as_ptr() as *const c_void, buffer.len())
@@ -81,6 +132,382 @@ impl FastText {
     Ok(())
   }
 
+  /// Loads a model from `reader`, e.g. a decompressing or network stream,
+  /// without holding all of its bytes in memory.
+  ///
+  /// The model is only replaced once the new one was read completely, so a
+  /// failed read leaves the current model loaded.
+  #[frb(ignore)]
+  pub fn load_model_from_reader<R: Read>(&mut self, reader: R) -> Result<(), FastTextError> {
+    let mut loaded = FastText::new()?;
+    let mut state = ReadState::new(reader);
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and `state` outlives the call.
+    let result = unsafe {
+      fasttext_load_model_from_reader(
+        loaded.handle,
+        Some(ReadState::<R>::trampoline),
+        &mut state as *mut ReadState<R> as *mut c_void,
+      )
+    };
+    // Read right away, so that the error the failed read caused is freed too.
+    let result = handle_result(result);
+    if let Some(payload) = state.take_panic() {
+      std::panic::resume_unwind(payload);
+    }
+    if let Some(e) = state.take_error() {
+      return Err(FastTextError::Io {
+        path: None,
+        message: format!("Failed to read the model: {}", e),
+      });
+    }
+    result?;
+
+    // The previous model is dropped along with `loaded`.
+    std::mem::swap(&mut self.handle, &mut loaded.handle);
+    Ok(())
+  }
+
+  /// Loads a model from the given path, reading the input matrix straight
+  /// from a read-only memory mapping of the file instead of copying it.
+  ///
+  /// Processes mapping the same file share its pages, and only the rows that
+  /// are used get read from disk. The file must not be modified while the
+  /// model is loaded. [`FastText::quantize`] copies the matrix out of the
+  /// mapping before changing it. Quantized models fail with
+  /// [`FastTextError::NotMappable`] and should be loaded with
+  /// [`FastText::load_model`] instead; the current model stays loaded.
+  ///
+  /// The input matrix is mapped wherever the dictionary leaves it in the
+  /// file. When that isn't aligned for floats, [`FastText::input_matrix`]
+  /// returns a copy of it.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The file path of the model to load.
+  pub fn load_model_mmap(&mut self, path: &str) -> Result<(), FastTextError> {
+    let c_path = to_c_string("path", path)?;
+    let mut loaded = FastText::new()?;
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and the CString is valid.
+    let result = unsafe { fasttext_load_model_mmap(loaded.handle, c_path.as_ptr()) };
+    handle_result(result).map_err(|e| e.at_path(path))?;
+
+    // The previous model is dropped along with `loaded`.
+    std::mem::swap(&mut self.handle, &mut loaded.handle);
+    Ok(())
+  }
+
+  /// Trains a supervised model, replacing whatever model this instance held.
+  ///
+  /// The trained model stays loaded, so it can be used for prediction or
+  /// saved with [`FastText::save_model`] right away.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - The training hyperparameters. `args.input` must point to a
+  ///   file with at least one label per line.
+  pub fn train_supervised(&mut self, args: TrainArgs) -> Result<(), FastTextError> {
+    self.train_model(args, ModelName::Supervised)
+  }
+
+  /// Trains skipgram word vectors, replacing whatever model this instance held.
+  ///
+  /// Start from [`TrainArgs::unsupervised`] to get the defaults of the
+  /// `fasttext skipgram` command.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - The training hyperparameters. `args.input` must point to a
+  ///   plain text file.
+  pub fn train_skipgram(&mut self, args: TrainArgs) -> Result<(), FastTextError> {
+    self.train_model(args, ModelName::Skipgram)
+  }
+
+  /// Trains cbow word vectors, replacing whatever model this instance held.
+  ///
+  /// Start from [`TrainArgs::unsupervised`] to get the defaults of the
+  /// `fasttext cbow` command.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - The training hyperparameters. `args.input` must point to a
+  ///   plain text file.
+  pub fn train_cbow(&mut self, args: TrainArgs) -> Result<(), FastTextError> {
+    self.train_model(args, ModelName::Cbow)
+  }
+
+  fn train_model(&mut self, args: TrainArgs, model: ModelName) -> Result<(), FastTextError> {
+    match self.train_typed(args, model, false, |_, _, _, _| ControlFlow::Continue(()))? {
+      TrainStatus::Completed => Ok(()),
+      TrainStatus::Aborted => Err(FastTextError::Aborted),
+    }
+  }
+
+  /// Trains from typed arguments, recording the training hash of a
+  /// deterministic training that completed.
+  fn train_typed<F>(
+    &mut self,
+    args: TrainArgs,
+    model: ModelName,
+    keep_partial_model: bool,
+    callback: F,
+  ) -> Result<TrainStatus, FastTextError>
+  where
+    F: FnMut(f32, f32, f64, f64) -> ControlFlow<()> + Send,
+  {
+    // Hashed first, so that a missing input is reported with its path.
+    let hash = if args.deterministic { Some(args.training_hash(model)?) } else { None };
+    let status = self.train_with_progress(&args.to_args(model)?, keep_partial_model, callback)?;
+    if let (TrainStatus::Completed, Some(hash)) = (status, hash) {
+      self.set_metadata(&format!("{}={}", TRAINING_HASH_KEY, hash))?;
+    }
+    Ok(status)
+  }
+
+  /// Same as [`FastText::train_with_progress`], for callers across the bridge.
+  ///
+  /// `callback` is awaited on every progress report, and the training stops
+  /// when it completes with `false`. Reports are serialized, so a slow
+  /// callback slows the training down. To stop the training from elsewhere,
+  /// get an [`AbortHandle`] with [`FastText::abort_handle`] before starting
+  /// it. Like [`FastText::train_supervised`], a deterministic training
+  /// records its training hash.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - The training hyperparameters.
+  /// * `model` - The kind of model to train.
+  /// * `keep_partial_model` - Whether an aborted training leaves the partially
+  ///   trained model loaded. Otherwise this instance is left without a model.
+  /// * `callback` - Receives every progress report, and returns whether to
+  ///   go on.
+  pub fn train_with_progress_callback(
+    &mut self,
+    args: TrainArgs,
+    model: ModelName,
+    keep_partial_model: bool,
+    callback: impl Fn(TrainProgress) -> DartFnFuture<bool> + Send + Sync + 'static,
+  ) -> Result<TrainStatus, FastTextError> {
+    self.train_typed(args, model, keep_partial_model, |progress, loss, lr, words_per_sec| {
+      if block_on(callback(TrainProgress { progress, loss, lr, words_per_sec })) {
+        ControlFlow::Continue(())
+      } else {
+        ControlFlow::Break(())
+      }
+    })
+  }
+
+  /// Trains a model from raw `fasttext::Args`, replacing whatever model this
+  /// instance held.
+  ///
+  /// This is the escape hatch for arguments built with [`Args::from_cli`];
+  /// the kind of model trained is `args.model()`.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - The training arguments.
+  pub fn train(&mut self, args: &Args) -> Result<(), FastTextError> {
+    match self.train_with_progress(args, false, |_, _, _, _| ControlFlow::Continue(()))? {
+      TrainStatus::Completed => Ok(()),
+      TrainStatus::Aborted => Err(FastTextError::Aborted),
+    }
+  }
+
+  /// Trains a model while reporting progress, replacing whatever model this
+  /// instance held.
+  ///
+  /// `callback` is called periodically from the training threads with the
+  /// progress in `[0, 1]`, the average loss, the current learning rate and
+  /// the number of words processed per second per thread. Returning
+  /// `ControlFlow::Break(())` stops the training, just like
+  /// [`AbortHandle::abort`] does.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - The training arguments.
+  /// * `keep_partial_model` - Whether an aborted training leaves the partially
+  ///   trained model loaded. Otherwise this instance is left without a model.
+  /// * `callback` - Receives `(progress, loss, lr, words_per_sec)`.
+  #[frb(ignore)]
+  pub fn train_with_progress<F>(
+    &mut self,
+    args: &Args,
+    keep_partial_model: bool,
+    callback: F,
+  ) -> Result<TrainStatus, FastTextError>
+  where
+    F: FnMut(f32, f32, f64, f64) -> ControlFlow<()> + Send,
+  {
+    let state = ProgressState::new(callback, self.abort.clone());
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and `state` outlives the call, which joins every training thread
+    // before returning.
+    let result = unsafe {
+      fasttext_train_with_callback(
+        self.handle,
+        args.handle(),
+        Some(ProgressState::<F>::trampoline),
+        &state as *const ProgressState<F> as *mut c_void,
+      )
+    };
+    self.abort.reset();
+    // Read right away, so that the error of an aborted training is freed too.
+    let result = handle_result(result);
+
+    if let Some(payload) = state.take_panic() {
+      self.reset()?;
+      std::panic::resume_unwind(payload);
+    }
+    if state.stopped() {
+      if !keep_partial_model {
+        self.reset()?;
+      }
+      return Ok(TrainStatus::Aborted);
+    }
+    result?;
+    Ok(TrainStatus::Completed)
+  }
+
+  /// Returns a handle that can stop a training of this instance from another thread.
+  pub fn abort_handle(&self) -> AbortHandle {
+    self.abort.clone()
+  }
+
+  /// Quantizes a supervised model in place, the equivalent of `fasttext quantize`.
+  ///
+  /// The model keeps working for predictions afterwards, and `save_model`
+  /// writes it in the compressed format usually stored as `.ftz`. Returns the
+  /// size in bytes of the quantized model once saved.
+  pub fn quantize(&mut self, args: QuantizeArgs) -> Result<u64, FastTextError> {
+    let qargs = args.to_args()?;
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and `qargs` outlives the call.
+    let result = unsafe { fasttext_quantize(self.handle, qargs.handle()) };
+    handle_result(result)?;
+
+    self.model_size()
+  }
+
+  /// Returns the size in bytes of the model as `save_model` would write it.
+  pub fn model_size(&self) -> Result<u64, FastTextError> {
+    // This is safe because we've checked the handle is not null on creation
+    let result = unsafe { fasttext_get_model_size(self.handle) };
+    let size = handle_result(result)?;
+
+    Ok(size as u64)
+  }
+
+  /// Searches the hyperparameters of a supervised model, like `fasttext supervised`
+  /// with the `-autotune-*` flags, and leaves the best model in this instance.
+  ///
+  /// # Arguments
+  ///
+  /// * `input` - The training file.
+  /// * `config` - The validation file, metric and budget of the search.
+  pub fn autotune(&mut self, input: &str, config: AutotuneConfig) -> Result<AutotuneResult, FastTextError> {
+    self.autotune_with_progress(input, config, |_| ControlFlow::Continue(()))
+  }
+
+  /// Same as [`FastText::autotune`], reporting every trial to `callback`.
+  ///
+  /// Breaking from the callback, or aborting through an [`AbortHandle`],
+  /// stops the search once the current trial is over. The model is then
+  /// trained with the best hyperparameters found so far, and the search
+  /// fails only if no trial succeeded.
+  #[frb(ignore)]
+  pub fn autotune_with_progress<F>(
+    &mut self,
+    input: &str,
+    config: AutotuneConfig,
+    callback: F,
+  ) -> Result<AutotuneResult, FastTextError>
+  where
+    F: FnMut(&AutotuneTrial) -> ControlFlow<()>,
+  {
+    let args = config.to_args(input)?;
+    let quantized = config.model_size.is_some();
+    let mut state = AutotuneState::new(callback, self.abort.clone(), quantized);
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and `args` and `state` outlive the call.
+    let result = unsafe {
+      fasttext_autotune(
+        self.handle,
+        args.handle(),
+        Some(AutotuneState::<F>::trampoline),
+        &mut state as *mut AutotuneState<F> as *mut c_void,
+      )
+    };
+    self.abort.reset();
+    // Read right away, so that the error is freed when the callback panicked.
+    let result = handle_result(result);
+
+    if let Some(payload) = state.take_panic() {
+      self.reset()?;
+      std::panic::resume_unwind(payload);
+    }
+    let summary = match result {
+      Ok(summary) => summary,
+      Err(e) => {
+        // Don't leave the model of whatever trial ran last.
+        self.reset()?;
+        return Err(e);
+      }
+    };
+
+    // This is safe because the C API allocates a fresh copy that we now own.
+    let best = unsafe { Args::from_raw(handle_result(fasttext_get_args(self.handle))?) };
+    Ok(AutotuneResult {
+      args: TrainArgs::from(&best),
+      quantize: quantized.then(|| QuantizeArgs::from(&best)),
+      score: summary.best_score,
+      trials: summary.trials,
+    })
+  }
+
+  /// Same as [`FastText::autotune_with_progress`], for callers across the bridge.
+  ///
+  /// `callback` is awaited after every trial, and the search stops when it
+  /// completes with `false`. To stop the search from elsewhere, get an
+  /// [`AbortHandle`] with [`FastText::abort_handle`] before starting it.
+  ///
+  /// # Arguments
+  ///
+  /// * `input` - The training file.
+  /// * `config` - The validation file, metric and budget of the search.
+  /// * `callback` - Receives every trial, and returns whether to go on.
+  pub fn autotune_with_progress_callback(
+    &mut self,
+    input: &str,
+    config: AutotuneConfig,
+    callback: impl Fn(AutotuneTrial) -> DartFnFuture<bool> + Send + Sync + 'static,
+  ) -> Result<AutotuneResult, FastTextError> {
+    self.autotune_with_progress(input, config, |trial| {
+      if block_on(callback(trial.clone())) {
+        ControlFlow::Continue(())
+      } else {
+        ControlFlow::Break(())
+      }
+    })
+  }
+
+  /// Replaces the underlying C++ object with a fresh one, dropping the model.
+  fn reset(&mut self) -> Result<(), FastTextError> {
+    let result = unsafe { fasttext_new() };
+    let handle = handle_result(result)?;
+    if handle.is_null() {
+      return Err(FastTextError::Cpp { message: "Failed to create fastText handle.".to_string() });
+    }
+    unsafe { fasttext_delete(self.handle) };
+    self.handle = handle;
+    Ok(())
+  }
+
   /// Predicts labels for a given text.
   ///
   /// # Arguments
@@ -88,9 +515,8 @@ impl FastText {
   /// * `text` - The input text for prediction.
   /// * `k` - The number of top predictions to return.
   /// * `threshold` - The minimum probability for a prediction to be returned.
-  pub fn predict(&self, text: &str, k: i32, threshold: f32) -> Result<Vec<Prediction>, String> {
-    let c_text = CString::new(text)
-      .map_err(|e| format!("Failed to create CString from text: {}", e))?;
+  pub fn predict(&self, text: &str, k: i32, threshold: f32) -> Result<Vec<Prediction>, FastTextError> {
+    let c_text = to_c_string("text", text)?;
 
     let mut n_predictions: usize = 0;
 
@@ -104,33 +530,221 @@ impl FastText {
       return if n_predictions == 0 {
         Ok(Vec::new())
       } else {
-        Err(
-          "fasttext_predict returned a null pointer but a non-zero prediction count."
+        Err(FastTextError::Cpp {
+          message: "fasttext_predict returned a null pointer but a non-zero prediction count."
             .to_string(),
-        )
+        })
       };
     }
 
-    // Create a safe slice, copy the data into a safe Rust Vec,
-    // and then immediately free the C-allocated memory.
+    // Copy the data into a safe Rust Vec, and then immediately free the
+    // C-allocated memory.
     let rust_predictions = unsafe {
-      let predictions_slice =
-        std::slice::from_raw_parts(preds_ptr, n_predictions);
-      let result = predictions_slice
+      let result = copy_predictions(preds_ptr, n_predictions);
+      fasttext_free_predictions(preds_ptr, n_predictions);
+      result
+    };
+
+    Ok(rust_predictions)
+  }
+
+  /// Predicts labels for a batch of texts.
+  ///
+  /// Same as calling [`FastText::predict`] on every text, but crossing into
+  /// C++ once for the whole batch. The result holds the predictions of each
+  /// text in the order of `texts`.
+  ///
+  /// # Arguments
+  ///
+  /// * `texts` - The input texts for prediction.
+  /// * `k` - The number of top predictions to return for each text.
+  /// * `threshold` - The minimum probability for a prediction to be returned.
+  pub fn predict_batch(
+    &self,
+    texts: &[&str],
+    k: i32,
+    threshold: f32,
+  ) -> Result<Vec<Vec<Prediction>>, FastTextError> {
+    self.predict_batch_with_threads(texts, k, threshold, 1)
+  }
+
+  /// Same as [`FastText::predict_batch`], splitting the batch over `threads`
+  /// threads that share the model.
+  ///
+  /// No more threads than there are cores or texts are started, so a large
+  /// `threads` is the same as using every core.
+  ///
+  /// # Arguments
+  ///
+  /// * `texts` - The input texts for prediction.
+  /// * `k` - The number of top predictions to return for each text.
+  /// * `threshold` - The minimum probability for a prediction to be returned.
+  /// * `threads` - The number of threads to predict with, at least 1.
+  pub fn predict_batch_with_threads(
+    &self,
+    texts: &[&str],
+    k: i32,
+    threshold: f32,
+    threads: i32,
+  ) -> Result<Vec<Vec<Prediction>>, FastTextError> {
+    if threads < 1 {
+      return Err(FastTextError::InvalidArgument {
+        message: "The number of threads must be positive.".to_string(),
+      });
+    }
+    let c_texts = texts
+      .iter()
+      .map(|text| to_c_string("texts", text))
+      .collect::<Result<Vec<_>, _>>()?;
+    let c_text_ptrs: Vec<*const c_char> = c_texts.iter().map(|text| text.as_ptr()).collect();
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and the CStrings outlive the call. The C API allocates one entry per
+    // text, which we must free.
+    let result = unsafe {
+      fasttext_predict_batch(
+        self.handle,
+        c_text_ptrs.as_ptr(),
+        c_text_ptrs.len(),
+        k,
+        threshold,
+        threads,
+      )
+    };
+    let batch_ptr = handle_result(result)?;
+    if batch_ptr.is_null() {
+      return Ok(Vec::new());
+    }
+
+    let rust_batch = unsafe {
+      let result = std::slice::from_raw_parts(batch_ptr, texts.len())
+        .iter()
+        .map(|p| copy_predictions(p.predictions, p.n_predictions))
+        .collect();
+      fasttext_free_predictions_batch(batch_ptr, texts.len());
+      result
+    };
+
+    Ok(rust_batch)
+  }
+
+  /// Explains a prediction by the contribution of each token to a label.
+  ///
+  /// The words of the text come first, in order, followed by the word
+  /// n-grams the model knows of. A newline ends the text, and shows up as a
+  /// `</s>` token of kind [`NgramKind::EndOfLine`]. A positive score pushes the text towards
+  /// `label`, a negative one away from it. Models trained with hierarchical
+  /// softmax can't be explained.
+  ///
+  /// # Arguments
+  ///
+  /// * `text` - The input text, as given to [`FastText::predict`].
+  /// * `label` - The label to explain, e.g. `__label__spam`.
+  pub fn explain(&self, text: &str, label: &str) -> Result<Vec<TokenContribution>, FastTextError> {
+    let c_text = to_c_string("text", text)?;
+    let c_label = to_c_string("label", label)?;
+
+    let mut n_contributions: usize = 0;
+
+    // The C API allocates memory for the contributions, which we must free.
+    let result = unsafe {
+      fasttext_explain(self.handle, c_text.as_ptr(), c_label.as_ptr(), &mut n_contributions)
+    };
+    let contributions_ptr = handle_result(result)?;
+    if contributions_ptr.is_null() {
+      return Ok(Vec::new());
+    }
+
+    // Copy the data into a safe Rust Vec, and then immediately free the
+    // C-allocated memory.
+    let rust_contributions = unsafe {
+      let result = std::slice::from_raw_parts(contributions_ptr, n_contributions)
         .iter()
-        .map(|p| {
-          let label_cstr = CStr::from_ptr(p.label);
-          Prediction {
-            probability: p.probability,
-            label: label_cstr.to_string_lossy().into_owned(),
-          }
+        .map(|c| TokenContribution {
+          token: CStr::from_ptr(c.token).to_string_lossy().into_owned(),
+          ngram_kind: match c.ngram_kind as u32 {
+            FASTTEXT_NGRAM_KIND_WORD_NGRAM => NgramKind::WordNgram,
+            FASTTEXT_NGRAM_KIND_EOS => NgramKind::EndOfLine,
+            _ => NgramKind::Word,
+          },
+          score: c.score,
         })
         .collect();
-      fasttext_free_predictions(preds_ptr, n_predictions);
+      fasttext_free_token_contributions(contributions_ptr, n_contributions);
       result
     };
 
-    Ok(rust_predictions)
+    Ok(rust_contributions)
+  }
+
+  /// Evaluates the model on a labelled test file, like `fasttext test-label`.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The test file, in the same format as the training file.
+  /// * `k` - The number of labels predicted for each example.
+  /// * `threshold` - The minimum probability for a label to be predicted.
+  pub fn evaluate(&self, path: &str, k: i32, threshold: f32) -> Result<EvaluationReport, FastTextError> {
+    let c_path = to_c_string("path", path)?;
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and the CString is valid.
+    let result = unsafe { fasttext_test(self.handle, c_path.as_ptr(), k, threshold) };
+    let meter = handle_result(result).map_err(|e| e.at_path(path))?;
+
+    // The meter is only read here and freed right after.
+    let report = unsafe { EvaluationReport::from_meter(meter, k, threshold) };
+    unsafe { fasttext_meter_delete(meter) };
+    Ok(report)
+  }
+
+  /// Evaluates the model on labelled test data read from `reader`.
+  ///
+  /// Same as [`FastText::evaluate`], for test sets that don't live in a file.
+  #[frb(ignore)]
+  pub fn evaluate_reader<R: Read>(
+    &self,
+    reader: R,
+    k: i32,
+    threshold: f32,
+  ) -> Result<EvaluationReport, FastTextError> {
+    let mut state = ReadState::new(reader);
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and `state` outlives the call.
+    let result = unsafe {
+      fasttext_test_from_reader(
+        self.handle,
+        Some(ReadState::<R>::trampoline),
+        &mut state as *mut ReadState<R> as *mut c_void,
+        k,
+        threshold,
+      )
+    };
+    // Read right away, so that the error or the meter is freed on every path.
+    let meter = handle_result(result);
+    let panic = state.take_panic();
+    let error = state.take_error();
+    if panic.is_some() || error.is_some() {
+      if let Ok(meter) = meter {
+        unsafe { fasttext_meter_delete(meter) };
+      }
+    }
+    if let Some(payload) = panic {
+      std::panic::resume_unwind(payload);
+    }
+    if let Some(e) = error {
+      return Err(FastTextError::Io {
+        path: None,
+        message: format!("Failed to read the test data: {}", e),
+      });
+    }
+    let meter = meter?;
+
+    // The meter is only read here and freed right after.
+    let report = unsafe { EvaluationReport::from_meter(meter, k, threshold) };
+    unsafe { fasttext_meter_delete(meter) };
+    Ok(report)
   }
 
   /// Nearest neighbors for a given word.
@@ -139,9 +753,8 @@ impl FastText {
   ///
   /// * `word` - The word to find nearest neighbors for.
   /// * `k` - The number of nearest neighbors to return.
-  pub fn get_nn(&self, word: &str, k: i32) -> Result<Vec<(f32, String)>, String> {
-    let c_word = CString::new(word)
-      .map_err(|e| format!("Failed to create CString from word: {}", e))?;
+  pub fn get_nn(&self, word: &str, k: i32) -> Result<Vec<(f32, String)>, FastTextError> {
+    let c_word = to_c_string("word", word)?;
 
     let mut n_neighbors: usize = 0;
 
@@ -155,10 +768,10 @@ impl FastText {
       return if n_neighbors == 0 {
         Ok(Vec::new())
       } else {
-        Err(
-          "fasttext_get_nn returned a null pointer but a non-zero prediction count."
+        Err(FastTextError::Cpp {
+          message: "fasttext_get_nn returned a null pointer but a non-zero prediction count."
             .to_string(),
-        )
+        })
       };
     }
 
@@ -192,13 +805,10 @@ impl FastText {
   /// * `wordA` - The word A in "A is to B as C is to ?".
   /// * `wordB` - The word B in "A is to B as C is to ?".
   /// * `wordC` - The word C in "A is to B as C is to ?".
-  pub fn get_analogies(&self, k: i32, word_a: &str, word_b: &str, word_c: &str,) -> Result<Vec<(f32, String)>, String> {
-    let c_word_a = CString::new(word_a)
-      .map_err(|e| format!("Failed to create CString from word_a: {}", e))?;
-    let c_word_b = CString::new(word_b)
-      .map_err(|e| format!("Failed to create CString from word_b: {}", e))?;
-    let c_word_c = CString::new(word_c)
-      .map_err(|e| format!("Failed to create CString from word_c: {}", e))?;
+  pub fn get_analogies(&self, k: i32, word_a: &str, word_b: &str, word_c: &str,) -> Result<Vec<(f32, String)>, FastTextError> {
+    let c_word_a = to_c_string("word_a", word_a)?;
+    let c_word_b = to_c_string("word_b", word_b)?;
+    let c_word_c = to_c_string("word_c", word_c)?;
 
     let mut n_analogies: usize = 0;
 
@@ -212,10 +822,10 @@ impl FastText {
       return if n_analogies == 0 {
         Ok(Vec::new())
       } else {
-        Err(
-          "get_analogies returned a null pointer but a non-zero prediction count."
+        Err(FastTextError::Cpp {
+          message: "get_analogies returned a null pointer but a non-zero prediction count."
             .to_string(),
-        )
+        })
       };
     }
 
@@ -242,9 +852,8 @@ impl FastText {
   /// # Arguments
   ///
   /// * `word` - The word to get the ID for.
-  pub fn get_word_id(&self, word: &str) -> Result<i32, String> {
-    let c_word = CString::new(word)
-      .map_err(|e| format!("Failed to create CString from word: {}", e))?;
+  pub fn get_word_id(&self, word: &str) -> Result<i32, FastTextError> {
+    let c_word = to_c_string("word", word)?;
 
     // This is safe because we've checked the handle is not null on creation,
     // and the CString is valid.
@@ -256,12 +865,14 @@ impl FastText {
 
   /// Get the subword ID of a word.
   ///
+  /// Returns -1 when the model was quantized with a `cutoff` that pruned the
+  /// subword's row.
+  ///
   /// # Arguments
   ///
   /// * `word` - The word to get the ID for.
-  pub fn get_subword_id(&self, word: &str) -> Result<i32, String> {
-    let c_word = CString::new(word)
-      .map_err(|e| format!("Failed to create CString from word: {}", e))?;
+  pub fn get_subword_id(&self, word: &str) -> Result<i32, FastTextError> {
+    let c_word = to_c_string("word", word)?;
 
     // This is safe because we've checked the handle is not null on creation,
     // and the CString is valid.
@@ -271,24 +882,228 @@ impl FastText {
     Ok(subword_id)
   }
 
+  /// Splits a word into the subwords its vector is built from.
+  ///
+  /// Returns each subword with its row in the input matrix. A word of the
+  /// dictionary comes first with its word ID, followed by its character
+  /// n-grams, which are wrapped in the `<` and `>` word boundary markers.
+  /// An out-of-vocabulary word only has its n-grams. N-grams pruned when
+  /// the model was quantized with a `cutoff` are left out.
+  ///
+  /// # Arguments
+  ///
+  /// * `word` - The word to split.
+  pub fn get_subwords(&self, word: &str) -> Result<Vec<(String, i32)>, FastTextError> {
+    let c_word = to_c_string("word", word)?;
+
+    let mut n_subwords: usize = 0;
+
+    // The C API allocates memory for the subwords, which we must free.
+    let result = unsafe { fasttext_get_subwords(self.handle, c_word.as_ptr(), &mut n_subwords) };
+    let subwords_ptr = handle_result(result)?;
+    if subwords_ptr.is_null() {
+      return Ok(Vec::new());
+    }
+
+    // Copy the data into a safe Rust Vec, and then immediately free the
+    // C-allocated memory.
+    let rust_subwords = unsafe {
+      let result = std::slice::from_raw_parts(subwords_ptr, n_subwords)
+        .iter()
+        .map(|s| (CStr::from_ptr(s.subword).to_string_lossy().into_owned(), s.id))
+        .collect();
+      fasttext_free_subwords(subwords_ptr, n_subwords);
+      result
+    };
+
+    Ok(rust_subwords)
+  }
+
+  /// Returns each subword of a word with its vector, like `fasttext print-ngrams`.
+  ///
+  /// The subwords are the ones of [`FastText::get_subwords`]. The vector of
+  /// the word is the average of these.
+  ///
+  /// # Arguments
+  ///
+  /// * `word` - The word to break down.
+  pub fn ngram_vectors(&self, word: &str) -> Result<Vec<(String, Vec<f32>)>, FastTextError> {
+    let c_word = to_c_string("word", word)?;
+    let dim = self.get_dimension()? as usize;
+
+    let mut n_ngrams: usize = 0;
+
+    // The C API allocates memory for the n-grams, which we must free.
+    let result = unsafe { fasttext_get_ngram_vectors(self.handle, c_word.as_ptr(), &mut n_ngrams) };
+    let ngrams_ptr = handle_result(result)?;
+    if ngrams_ptr.is_null() {
+      return Ok(Vec::new());
+    }
+
+    // Copy the data into a safe Rust Vec, and then immediately free the
+    // C-allocated memory. Every vector holds `dim` floats.
+    let rust_ngrams = unsafe {
+      let result = std::slice::from_raw_parts(ngrams_ptr, n_ngrams)
+        .iter()
+        .map(|n| {
+          (
+            CStr::from_ptr(n.ngram).to_string_lossy().into_owned(),
+            std::slice::from_raw_parts(n.vector, dim).to_vec(),
+          )
+        })
+        .collect();
+      fasttext_free_ngram_vectors(ngrams_ptr, n_ngrams);
+      result
+    };
+
+    Ok(rust_ngrams)
+  }
+
   /// Saves a model to the given path.
   ///
   /// # Arguments
   ///
   /// * `path` - The file path of where to save the model.
-  pub fn save_model(&mut self, path: &str) -> Result<(), String> {
-    let c_path = CString::new(path)
-      .map_err(|e| format!("Failed to create CString from path: {}", e))?;
+  pub fn save_model(&mut self, path: &str) -> Result<(), FastTextError> {
+    let c_path = to_c_string("path", path)?;
 
     // This is safe because we've checked the handle is not null on creation,
     // and the CString is valid.
     let result = unsafe { fasttext_save_model(self.handle, c_path.as_ptr()) };
-    handle_result(result)?;
+    handle_result(result).map_err(|e| e.at_path(path))?;
+    Ok(())
+  }
+
+  /// Serializes the model in the `.bin` format, like [`FastText::save_model`],
+  /// without going through a file.
+  ///
+  /// The bytes can be loaded back with [`FastText::load_model_from_buffer`].
+  pub fn save_model_to_vec(&self) -> Result<Vec<u8>, FastTextError> {
+    let mut buffer = Vec::new();
+    self.save_model_to_writer(&mut buffer)?;
+    Ok(buffer)
+  }
+
+  /// Serializes the model in the `.bin` format to `writer`, which is flushed
+  /// once the whole model was written.
+  #[frb(ignore)]
+  pub fn save_model_to_writer<W: Write>(&self, writer: W) -> Result<(), FastTextError> {
+    let mut state = WriteState::new(writer);
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and `state` outlives the call.
+    let result = unsafe {
+      fasttext_save_model_to_writer(
+        self.handle,
+        Some(WriteState::<W>::trampoline),
+        &mut state as *mut WriteState<W> as *mut c_void,
+      )
+    };
+    // Read right away, so that the error the failed write caused is freed too.
+    let result = handle_result(result);
+    if let Some(payload) = state.take_panic() {
+      std::panic::resume_unwind(payload);
+    }
+    let io_error = |e: std::io::Error| FastTextError::Io {
+      path: None,
+      message: format!("Failed to write the model: {}", e),
+    };
+    if let Some(e) = state.take_error() {
+      return Err(io_error(e));
+    }
+    result?;
+    state.flush().map_err(io_error)
+  }
+
+  /// Saves the output matrix in the `.vec` format, like `-saveOutput`.
+  ///
+  /// The rows are named after the labels of a supervised model and after
+  /// the words otherwise. Quantized models fail with `QuantizedUnsupported`.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The file to write the vectors to.
+  pub fn save_output(&self, path: &str) -> Result<(), FastTextError> {
+    let c_path = to_c_string("path", path)?;
+
+    // This is safe because we've checked the handle is not null on creation,
+    // and the CString is valid.
+    let result = unsafe { fasttext_save_output(self.handle, c_path.as_ptr()) };
+    handle_result(result).map_err(|e| e.at_path(path))?;
     Ok(())
   }
 
+  /// Returns every label of a supervised model with its row of the output
+  /// matrix, in the order of [`FastText::labels`].
+  ///
+  /// With hierarchical softmax, the rows belong to the nodes of the tree
+  /// rather than to the labels, which fails with `InvalidArgument`.
+  pub fn label_vectors(&self) -> Result<Vec<(String, Vec<f32>)>, FastTextError> {
+    let args = self.args()?;
+    if args.model != ModelName::Supervised {
+      return Err(FastTextError::NotSupervised);
+    }
+    if args.loss == Loss::Hs {
+      return Err(FastTextError::InvalidArgument {
+        message: "The output matrix of a hierarchical softmax has no row per label.".to_string(),
+      });
+    }
+
+    let output = self.output_matrix()?;
+    Ok(
+      self
+        .labels()?
+        .into_iter()
+        .enumerate()
+        .map(|(i, (label, _))| (label, output.row(i).to_vec()))
+        .collect(),
+    )
+  }
+
+  /// Exports the vectors of the words of the dictionary, like
+  /// `fasttext print-word-vectors` does for the `.vec` file of a model.
+  ///
+  /// The vectors are computed and written one word at a time. The dictionary
+  /// keeps its words sorted by frequency, so `top_n` keeps the most frequent.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The file to write the vectors to.
+  /// * `format` - The file format to write.
+  /// * `top_n` - Only export this many words, all of them if `None`.
+  pub fn export_vectors(
+    &self,
+    path: &str,
+    format: VectorFormat,
+    top_n: Option<usize>,
+  ) -> Result<(), FastTextError> {
+    let mut words = self.words()?;
+    if let Some(n) = top_n {
+      words.truncate(n);
+    }
+    let dim = self.get_dimension()? as usize;
+
+    let io_error = |e: std::io::Error| FastTextError::Io {
+      path: Some(path.to_string()),
+      message: format!("Failed to write the vectors: {}", e),
+    };
+    let file = File::create(path).map_err(io_error)?;
+    let mut writer = VectorWriter::new(BufWriter::new(file), format, words.len(), dim).map_err(io_error)?;
+
+    let mut vector = vec![0.0; dim];
+    for (word, _) in &words {
+      let c_word = to_c_string("word", word)?;
+      // This is safe because we've checked the handle is not null on creation,
+      // and `vector` holds `dim` floats.
+      let result = unsafe { fasttext_get_word_vector(self.handle, c_word.as_ptr(), vector.as_mut_ptr()) };
+      handle_result(result)?;
+      writer.write_row(word, &vector).map_err(io_error)?;
+    }
+    writer.finish().map_err(io_error)
+  }
+
   /// Get dimension of the model.
-  pub fn get_dimension(&self) -> Result<i32, String> {
+  pub fn get_dimension(&self) -> Result<i32, FastTextError> {
     // This is safe because we've checked the handle is not null on creation
     let result = unsafe { fasttext_get_dimension(self.handle) };
     let dimension = handle_result(result)?;
@@ -296,16 +1111,124 @@ impl FastText {
     Ok(dimension)
   }
 
+  /// Borrows the input matrix, holding one row per word and n-gram bucket.
+  ///
+  /// No data is copied, unless the matrix was memory-mapped from a file
+  /// where it isn't aligned for floats. Quantized models fail with
+  /// `QuantizedUnsupported`.
+  #[frb(ignore)]
+  pub fn input_matrix(&self) -> Result<MatrixView<'_>, FastTextError> {
+    // This is safe because we've checked the handle is not null on creation.
+    let matrix = handle_result(unsafe { fasttext_get_input_matrix(self.handle) })?;
+    // The matrix lives as long as the model, which can only be replaced
+    // through `&mut self`.
+    Ok(unsafe { MatrixView::from_c(matrix) })
+  }
+
+  /// Borrows the output matrix, holding one row per label for supervised
+  /// models and one per word otherwise.
+  ///
+  /// No data is copied. Models quantized with `qout` fail with
+  /// `QuantizedUnsupported`.
+  #[frb(ignore)]
+  pub fn output_matrix(&self) -> Result<MatrixView<'_>, FastTextError> {
+    // This is safe because we've checked the handle is not null on creation.
+    let matrix = handle_result(unsafe { fasttext_get_output_matrix(self.handle) })?;
+    // The matrix lives as long as the model, which can only be replaced
+    // through `&mut self`.
+    Ok(unsafe { MatrixView::from_c(matrix) })
+  }
+
+  /// Returns the hyperparameters the model was trained with.
+  pub fn args(&self) -> Result<ModelArgs, FastTextError> {
+    // This is safe because the C API allocates a fresh copy that we now own.
+    let args = unsafe { Args::from_raw(handle_result(fasttext_get_args(self.handle))?) };
+    // This is safe because we've checked the handle is not null on creation
+    let quantized = handle_result(unsafe { fasttext_is_quant(self.handle) })?;
+
+    Ok(ModelArgs::new(&args, quantized))
+  }
+
+  /// Returns the [`TrainArgs::training_hash`] saved with a deterministically
+  /// trained model, or `None` for any other model. Quantizing a model drops
+  /// its hash, since the hash doesn't cover the quantization.
+  ///
+  /// To verify a model, retrain it with arguments that give the same hash
+  /// and compare the saved files.
+  pub fn training_hash(&self) -> Result<Option<String>, FastTextError> {
+    let metadata = self.metadata()?;
+    Ok(metadata.lines().find_map(|line| {
+      let (key, value) = line.split_once('=')?;
+      (key == TRAINING_HASH_KEY).then(|| value.to_string())
+    }))
+  }
+
+  /// The free-form text saved at the end of the model file, `key=value` lines.
+  fn metadata(&self) -> Result<String, FastTextError> {
+    // The C API returns a copy, which we must free.
+    let metadata = handle_result(unsafe { fasttext_get_metadata(self.handle) })?;
+    let result = unsafe { CStr::from_ptr(metadata).to_string_lossy().into_owned() };
+    unsafe { fasttext_free_string(metadata) };
+    Ok(result)
+  }
+
+  fn set_metadata(&mut self, metadata: &str) -> Result<(), FastTextError> {
+    let c_metadata = to_c_string("metadata", metadata)?;
+    // This is safe because we've checked the handle is not null on creation
+    let result = unsafe { fasttext_set_metadata(self.handle, c_metadata.as_ptr()) };
+    handle_result(result)?;
+    Ok(())
+  }
+
+  /// Returns the number of words in the dictionary of the model.
+  pub fn nwords(&self) -> Result<i32, FastTextError> {
+    // This is safe because we've checked the handle is not null on creation
+    let result = unsafe { fasttext_get_nwords(self.handle) };
+    handle_result(result)
+  }
+
+  /// Returns the number of labels in the dictionary of the model.
+  pub fn nlabels(&self) -> Result<i32, FastTextError> {
+    // This is safe because we've checked the handle is not null on creation
+    let result = unsafe { fasttext_get_nlabels(self.handle) };
+    handle_result(result)
+  }
+
+  /// Returns the number of tokens, words and labels, the model was trained on.
+  pub fn ntokens(&self) -> Result<i64, FastTextError> {
+    // This is safe because we've checked the handle is not null on creation
+    let result = unsafe { fasttext_get_ntokens(self.handle) };
+    handle_result(result)
+  }
+
+  /// Returns the words of the dictionary with the number of times each was
+  /// seen in training, in the order of their IDs.
+  pub fn words(&self) -> Result<Vec<(String, i64)>, FastTextError> {
+    let mut n_words: usize = 0;
+
+    // The C API allocates memory for the words, which we must free.
+    let result = unsafe { fasttext_get_words(self.handle, &mut n_words) };
+    copy_tokens(result, n_words)
+  }
+
+  /// Returns the labels of the dictionary, e.g. `__label__baking`, with the
+  /// number of times each was seen in training.
+  pub fn labels(&self) -> Result<Vec<(String, i64)>, FastTextError> {
+    let mut n_labels: usize = 0;
+
+    // The C API allocates memory for the labels, which we must free.
+    let result = unsafe { fasttext_get_labels(self.handle, &mut n_labels) };
+    copy_tokens(result, n_labels)
+  }
+
   /// Get the vector of a word.
   ///
   /// # Arguments
   ///
   /// * `word` - The word to get the vector for.
-  pub fn get_word_vector(&self, word: &str) -> Result<Vec<f32>, String> {
-    let c_word = CString::new(word)
-      .map_err(|e| format!("Failed to create CString from word: {}", e))?;
-    let dim = self.get_dimension()
-      .map_err(|e| format!("Failed to get dimension of model: {}", e))?;
+  pub fn get_word_vector(&self, word: &str) -> Result<Vec<f32>, FastTextError> {
+    let c_word = to_c_string("word", word)?;
+    let dim = self.get_dimension()?;
 
     let mut vec: Vec<f32> = vec![0.0; dim as usize];
 
@@ -324,11 +1247,9 @@ impl FastText {
   /// # Arguments
   ///
   /// * `text` - The sentence to get the vector for.
-  pub fn get_sentence_vector(&self, text: &str) -> Result<Vec<f32>, String> {
-    let c_word = CString::new(text)
-      .map_err(|e| format!("Failed to create CString from text: {}", e))?;
-    let dim = self.get_dimension()
-      .map_err(|e| format!("Failed to get dimension of model: {}", e))?;
+  pub fn get_sentence_vector(&self, text: &str) -> Result<Vec<f32>, FastTextError> {
+    let c_word = to_c_string("text", text)?;
+    let dim = self.get_dimension()?;
 
     let mut vec: Vec<f32> = vec![0.0; dim as usize];
 
@@ -343,6 +1264,47 @@ impl FastText {
   }
 }
 
+/// Copies predictions allocated by the C API into Rust.
+///
+/// # Safety
+///
+/// `predictions` must point to `n_predictions` valid predictions, or be null
+/// when there are none.
+unsafe fn copy_predictions(
+  predictions: *const fasttext_prediction_t,
+  n_predictions: usize,
+) -> Vec<Prediction> {
+  if n_predictions == 0 {
+    return Vec::new();
+  }
+  std::slice::from_raw_parts(predictions, n_predictions)
+    .iter()
+    .map(|p| Prediction {
+      probability: p.probability,
+      label: CStr::from_ptr(p.label).to_string_lossy().into_owned(),
+    })
+    .collect()
+}
+
+/// Copies the words or labels returned by the C API into Rust, and frees them.
+fn copy_tokens(result: TokensResult, n_tokens: usize) -> Result<Vec<(String, i64)>, FastTextError> {
+  let tokens_ptr = handle_result(result)?;
+  if tokens_ptr.is_null() {
+    return Ok(Vec::new());
+  }
+
+  let rust_tokens = unsafe {
+    let result = std::slice::from_raw_parts(tokens_ptr, n_tokens)
+      .iter()
+      .map(|t| (CStr::from_ptr(t.token).to_string_lossy().into_owned(), t.count))
+      .collect();
+    fasttext_free_tokens(tokens_ptr, n_tokens);
+    result
+  };
+
+  Ok(rust_tokens)
+}
+
 // The Drop trait implementation is the heart of RAII.
 // When a `FastText` object goes out of scope, this `drop` method is called automatically.
 impl Drop for FastText {
@@ -0,0 +1,111 @@
+use std::any::Any;
+use std::ffi::{c_char, c_void};
+use std::io::{self, ErrorKind, Read, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Lets the C++ side pull bytes from a Rust reader through a `fasttext_read_callback_t`.
+pub(crate) struct ReadState<R> {
+  reader: R,
+  error: Option<io::Error>,
+  panic: Option<Box<dyn Any + Send>>,
+}
+
+impl<R: Read> ReadState<R> {
+  pub(crate) fn new(reader: R) -> Self {
+    ReadState { reader, error: None, panic: None }
+  }
+
+  /// Takes the error returned by the reader, if any.
+  pub(crate) fn take_error(&mut self) -> Option<io::Error> {
+    self.error.take()
+  }
+
+  /// Takes the payload of a panic raised by the reader, if any.
+  pub(crate) fn take_panic(&mut self) -> Option<Box<dyn Any + Send>> {
+    self.panic.take()
+  }
+
+  /// The `fasttext_read_callback_t` handed to the C API along with `self` as user data.
+  ///
+  /// Errors and panics are stored for the caller to pick up once the C call
+  /// returns, and reported to C++ as a failed read.
+  pub(crate) unsafe extern "C" fn trampoline(
+    buffer: *mut c_char,
+    size: usize,
+    user_data: *mut c_void,
+  ) -> i64 {
+    let state = unsafe { &mut *(user_data as *mut Self) };
+    let buffer = unsafe { std::slice::from_raw_parts_mut(buffer as *mut u8, size) };
+
+    let result = catch_unwind(AssertUnwindSafe(|| loop {
+      match state.reader.read(buffer) {
+        Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+        result => break result,
+      }
+    }));
+    match result {
+      Ok(Ok(n)) => n as i64,
+      Ok(Err(e)) => {
+        state.error = Some(e);
+        -1
+      }
+      Err(payload) => {
+        state.panic = Some(payload);
+        -1
+      }
+    }
+  }
+}
+
+/// Lets the C++ side push bytes to a Rust writer through a `fasttext_write_callback_t`.
+pub(crate) struct WriteState<W> {
+  writer: W,
+  error: Option<io::Error>,
+  panic: Option<Box<dyn Any + Send>>,
+}
+
+impl<W: Write> WriteState<W> {
+  pub(crate) fn new(writer: W) -> Self {
+    WriteState { writer, error: None, panic: None }
+  }
+
+  /// Takes the error returned by the writer, if any.
+  pub(crate) fn take_error(&mut self) -> Option<io::Error> {
+    self.error.take()
+  }
+
+  /// Takes the payload of a panic raised by the writer, if any.
+  pub(crate) fn take_panic(&mut self) -> Option<Box<dyn Any + Send>> {
+    self.panic.take()
+  }
+
+  /// Flushes the writer once the C++ side is done with it.
+  pub(crate) fn flush(&mut self) -> io::Result<()> {
+    self.writer.flush()
+  }
+
+  /// The `fasttext_write_callback_t` handed to the C API along with `self` as user data.
+  ///
+  /// Errors and panics are stored for the caller to pick up once the C call
+  /// returns, and reported to C++ as a failed write.
+  pub(crate) unsafe extern "C" fn trampoline(
+    buffer: *const c_char,
+    size: usize,
+    user_data: *mut c_void,
+  ) -> bool {
+    let state = unsafe { &mut *(user_data as *mut Self) };
+    let buffer = unsafe { std::slice::from_raw_parts(buffer as *const u8, size) };
+
+    match catch_unwind(AssertUnwindSafe(|| state.writer.write_all(buffer))) {
+      Ok(Ok(())) => true,
+      Ok(Err(e)) => {
+        state.error = Some(e);
+        false
+      }
+      Err(payload) => {
+        state.panic = Some(payload);
+        false
+      }
+    }
+  }
+}
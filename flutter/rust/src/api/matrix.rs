@@ -0,0 +1,49 @@
+use fasttext_bindings::bindings::fasttext_matrix_t;
+use flutter_rust_bridge::frb;
+use std::borrow::Cow;
+
+/// A borrowed view of a dense matrix of a model, stored row after row.
+///
+/// The view points into the memory of the model, so it can't outlive the
+/// [`FastText`](crate::api::fasttext::FastText) instance it came from nor be
+/// kept while a model is loaded, trained or quantized in its place.
+#[frb(ignore)]
+#[derive(Debug, Clone)]
+pub struct MatrixView<'a> {
+  pub rows: usize,
+  pub cols: usize,
+  /// The `rows * cols` values of the matrix, copied only when a memory-mapped
+  /// matrix isn't aligned for floats.
+  pub data: Cow<'a, [f32]>,
+}
+
+impl<'a> MatrixView<'a> {
+  /// Borrows the matrix described by the C API.
+  ///
+  /// # Safety
+  ///
+  /// `matrix.data` must point to `rows * cols` floats, which may be
+  /// unaligned, that stay valid and unchanged for `'a`.
+  pub(crate) unsafe fn from_c(matrix: fasttext_matrix_t) -> Self {
+    let rows = matrix.rows as usize;
+    let cols = matrix.cols as usize;
+    let data = if rows * cols == 0 {
+      Cow::Borrowed(&[][..])
+    } else if matrix.data.is_aligned() {
+      Cow::Borrowed(std::slice::from_raw_parts(matrix.data, rows * cols))
+    } else {
+      Cow::Owned((0..rows * cols).map(|i| matrix.data.add(i).read_unaligned()).collect())
+    };
+    MatrixView { rows, cols, data }
+  }
+
+  /// Returns the row at `index`.
+  ///
+  /// # Panics
+  ///
+  /// If `index` isn't lower than `rows`.
+  pub fn row(&self, index: usize) -> &[f32] {
+    assert!(index < self.rows, "row {} out of {}", index, self.rows);
+    &self.data[index * self.cols..(index + 1) * self.cols]
+  }
+}
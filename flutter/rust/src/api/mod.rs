@@ -1,4 +1,22 @@
+#[cfg(feature = "cpp")]
+pub mod args;
+#[cfg(feature = "cpp")]
+pub mod autotune;
+pub mod error;
+#[cfg(feature = "cpp")]
+pub mod evaluate;
+#[cfg(feature = "cpp")]
 pub mod fasttext;
+#[cfg(feature = "cpp")]
+mod io;
+#[cfg(feature = "cpp")]
+pub mod matrix;
+#[cfg(feature = "pure-rust")]
+pub mod native;
+pub mod prediction;
+#[cfg(feature = "cpp")]
+pub mod train;
+pub mod vectors;
 
 #[flutter_rust_bridge::frb(init)]
 pub fn init_app() {
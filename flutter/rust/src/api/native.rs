@@ -0,0 +1,949 @@
+use crate::api::error::FastTextError;
+pub use crate::api::prediction::Prediction;
+use flutter_rust_bridge::frb;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::sync::OnceLock;
+
+const FILEFORMAT_MAGIC: i32 = 793712314;
+const VERSION: i32 = 12;
+
+const EOS: &[u8] = b"</s>";
+const BOW: &[u8] = b"<";
+const EOW: &[u8] = b">";
+// `Args::load` doesn't read the label prefix, so models always use the default.
+const LABEL: &[u8] = b"__label__";
+
+const SIGMOID_TABLE_SIZE: usize = 512;
+const MAX_SIGMOID: f32 = 8.0;
+// The number of centroids of each product quantizer.
+const KSUB: usize = 256;
+// Floats are read this many at a time, so that a corrupt size can't make us
+// allocate more than the file holds.
+const CHUNK: usize = 1 << 16;
+
+/// A fastText model read and queried in Rust, without the C++ library.
+///
+/// It loads the same `.bin` and `.ftz` files as `FastText::load_model`, and
+/// its predictions and vectors are computed with the same `f32` operations
+/// in the same order, so they match those of the C++ bit for bit. Only
+/// inference is supported: there's no training, quantization or testing.
+pub struct NativeModel {
+  args: StoredArgs,
+  dict: Dictionary,
+  input: Matrix,
+  output: Matrix,
+  quant: bool,
+  sigmoid: Vec<f32>,
+  // Only built for the hierarchical softmax.
+  tree: Vec<Node>,
+  // The normalized vector of each word, computed on the first neighbor query.
+  word_vectors: OnceLock<Vec<f32>>,
+}
+
+/// The part of fastText's `Args` that is saved with a model.
+struct StoredArgs {
+  dim: i32,
+  loss: i32,
+  model: i32,
+}
+
+// The values of fastText's `model_name` and `loss_name` enums.
+const MODEL_SUPERVISED: i32 = 3;
+const LOSS_HS: i32 = 1;
+const LOSS_SOFTMAX: i32 = 3;
+
+struct Entry {
+  word: Vec<u8>,
+  count: i64,
+  is_label: bool,
+}
+
+struct Dictionary {
+  nwords: i32,
+  nlabels: i32,
+  words: Vec<Entry>,
+  ids: HashMap<Vec<u8>, i32>,
+  // -1 unless the model was quantized with a cutoff, then the n-grams that
+  // were kept are remapped and every other one is dropped.
+  pruneidx_size: i64,
+  pruneidx: HashMap<i32, i32>,
+  minn: i32,
+  maxn: i32,
+  bucket: i32,
+  word_ngrams: i32,
+}
+
+enum Matrix {
+  Dense { rows: usize, cols: usize, data: Vec<f32> },
+  Quant(QuantMatrix),
+}
+
+struct QuantMatrix {
+  rows: usize,
+  cols: usize,
+  codes: Vec<u8>,
+  pq: ProductQuantizer,
+  norms: Option<(Vec<u8>, ProductQuantizer)>,
+}
+
+struct ProductQuantizer {
+  nsubq: usize,
+  dsub: usize,
+  lastdsub: usize,
+  centroids: Vec<f32>,
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+  left: i32,
+  right: i32,
+  count: i64,
+}
+
+impl NativeModel {
+  /// Loads a model from the given path.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The file path of the `.bin` or `.ftz` model to load.
+  pub fn load_model(path: &str) -> Result<Self, FastTextError> {
+    let file = File::open(path).map_err(|_| FastTextError::Io {
+      path: Some(path.to_string()),
+      message: format!("{} cannot be opened for loading!", path),
+    })?;
+    Self::read(BufReader::new(file), path).map_err(|e| e.at_path(path))
+  }
+
+  /// Loads a model from `reader`, e.g. a decompressing or network stream.
+  #[frb(ignore)]
+  pub fn load_model_from_reader<R: Read>(reader: R) -> Result<Self, FastTextError> {
+    Self::read(reader, "Model from reader")
+  }
+
+  fn read<R: Read>(reader: R, name: &str) -> Result<Self, FastTextError> {
+    let mut reader = ModelReader { reader };
+    read_model(&mut reader, name).map_err(|e| match e.kind() {
+      io::ErrorKind::InvalidData => FastTextError::InvalidModelFormat { message: e.to_string() },
+      io::ErrorKind::UnexpectedEof => {
+        FastTextError::InvalidModelFormat { message: format!("{} is truncated!", name) }
+      }
+      _ => FastTextError::Io { path: None, message: format!("Failed to read the model: {}", e) },
+    })
+  }
+
+  /// The dimension of the word vectors.
+  pub fn get_dimension(&self) -> i32 {
+    self.args.dim
+  }
+
+  /// Whether the model is quantized, i.e. loaded from a `.ftz` file.
+  pub fn is_quant(&self) -> bool {
+    self.quant
+  }
+
+  /// Predicts the `k` most likely labels of `text`, like `FastText::predict`.
+  ///
+  /// # Arguments
+  ///
+  /// * `text` - The text to classify. Only its first line is used.
+  /// * `k` - The number of labels to return, -1 for all of them.
+  /// * `threshold` - The minimum probability of the labels to return.
+  pub fn predict(&self, text: &str, k: i32, threshold: f32) -> Result<Vec<Prediction>, FastTextError> {
+    if self.args.model != MODEL_SUPERVISED {
+      return Err(FastTextError::NotSupervised);
+    }
+    let words = self.dict.get_line(text.as_bytes());
+    if words.is_empty() {
+      return Ok(Vec::new());
+    }
+    let k = match k {
+      -1 => self.output.rows(),
+      k if k > 0 => k as usize,
+      _ => {
+        return Err(FastTextError::InvalidArgument { message: "k needs to be 1 or higher!".to_string() })
+      }
+    };
+
+    let hidden = self.input.average_rows(&words);
+    let mut heap = TopK::new(k);
+    if self.args.loss == LOSS_HS {
+      let root = 2 * self.dict.nlabels - 2;
+      self.dfs(&mut heap, threshold, root, 0.0, &hidden);
+    } else {
+      for (i, probability) in self.compute_output(&hidden).into_iter().enumerate() {
+        if probability >= threshold {
+          heap.push(std_log(probability), i as i32);
+        }
+      }
+    }
+    Ok(
+      heap
+        .into_sorted()
+        .into_iter()
+        .map(|(score, label)| Prediction {
+          probability: score.exp(),
+          label: self.dict.label(label),
+        })
+        .collect(),
+    )
+  }
+
+  /// Get the vector of a word, averaging the vectors of its character n-grams.
+  ///
+  /// # Arguments
+  ///
+  /// * `word` - The word to get the vector for.
+  pub fn get_word_vector(&self, word: &str) -> Result<Vec<f32>, FastTextError> {
+    Ok(self.word_vector(word.as_bytes()))
+  }
+
+  /// Get the vector of a sentence.
+  ///
+  /// # Arguments
+  ///
+  /// * `text` - The sentence to get the vector for. Only its first line is used.
+  pub fn get_sentence_vector(&self, text: &str) -> Result<Vec<f32>, FastTextError> {
+    let dim = self.args.dim as usize;
+    if self.args.model == MODEL_SUPERVISED {
+      let line = self.dict.get_line(text.as_bytes());
+      if line.is_empty() {
+        return Ok(vec![0.0; dim]);
+      }
+      return Ok(self.input.average_rows(&line));
+    }
+
+    let mut sentence = vec![0.0; dim];
+    let mut count = 0;
+    let line = text.as_bytes().split(|c| *c == b'\n').next().unwrap_or_default();
+    for word in line.split(|c| c.is_ascii_whitespace() || *c == b'\x0b').filter(|w| !w.is_empty()) {
+      let mut vec = self.word_vector(word);
+      let norm = norm(&vec);
+      if norm > 0.0 {
+        scale(&mut vec, 1.0 / norm as f64);
+        sentence.iter_mut().zip(&vec).for_each(|(s, v)| *s += v);
+        count += 1;
+      }
+    }
+    if count > 0 {
+      scale(&mut sentence, 1.0 / count as f64);
+    }
+    Ok(sentence)
+  }
+
+  /// Nearest neighbors for a given word, by cosine similarity.
+  ///
+  /// Unlike fastText, which reads an empty heap, a `k` of 0 or lower has no
+  /// neighbors.
+  ///
+  /// # Arguments
+  ///
+  /// * `word` - The word to find nearest neighbors for.
+  /// * `k` - The number of nearest neighbors to return.
+  pub fn get_nn(&self, word: &str, k: i32) -> Result<Vec<(f32, String)>, FastTextError> {
+    let query = self.word_vector(word.as_bytes());
+    let dim = self.args.dim as usize;
+    let vectors = self.word_vectors.get_or_init(|| {
+      // Like `FastText::precomputeWordVectors`, which adds the scaled vector
+      // to a row of zeros.
+      let mut vectors = vec![0.0; self.dict.nwords as usize * dim];
+      for (entry, row) in self.dict.words.iter().zip(vectors.chunks_mut(dim.max(1))) {
+        let vec = self.word_vector(&entry.word);
+        let norm = norm(&vec);
+        if norm > 0.0 {
+          let a = (1.0 / norm as f64) as f32;
+          row.iter_mut().zip(&vec).for_each(|(r, v)| *r += a * v);
+        }
+      }
+      vectors
+    });
+    if k <= 0 {
+      return Ok(Vec::new());
+    }
+    let mut query_norm = norm(&query);
+    if query_norm.abs() < 1e-8 {
+      query_norm = 1.0;
+    }
+
+    let mut heap = TopK::new(k as usize);
+    for (i, entry) in self.dict.words.iter().take(self.dict.nwords as usize).enumerate() {
+      if entry.word != word.as_bytes() {
+        let row = &vectors[i * dim..(i + 1) * dim];
+        let dp = row.iter().zip(&query).fold(0.0f32, |d, (a, b)| d + a * b);
+        heap.push(dp / query_norm, i as i32);
+      }
+    }
+    Ok(
+      heap
+        .into_sorted()
+        .into_iter()
+        .map(|(similarity, i)| (similarity, String::from_utf8_lossy(&self.dict.words[i as usize].word).into_owned()))
+        .collect(),
+    )
+  }
+
+  /// Returns each subword with its row in the input matrix, like
+  /// `FastText::get_subwords`.
+  ///
+  /// # Arguments
+  ///
+  /// * `word` - The word to split.
+  pub fn get_subwords(&self, word: &str) -> Result<Vec<(String, i32)>, FastTextError> {
+    let word = word.as_bytes();
+    let mut ngrams = Vec::new();
+    let mut substrings = Vec::new();
+    let id = self.dict.get_id(word);
+    if id >= 0 {
+      ngrams.push(id);
+      substrings.push(self.dict.words[id as usize].word.clone());
+    }
+    if word != EOS {
+      self.dict.compute_subwords(&[BOW, word, EOW].concat(), &mut ngrams, Some(&mut substrings));
+    }
+    Ok(
+      substrings
+        .into_iter()
+        .zip(ngrams)
+        .map(|(substring, id)| (String::from_utf8_lossy(&substring).into_owned(), id))
+        .collect(),
+    )
+  }
+
+  fn word_vector(&self, word: &[u8]) -> Vec<f32> {
+    let mut ngrams = Vec::new();
+    let id = self.dict.get_id(word);
+    if id >= 0 {
+      ngrams.push(id);
+    }
+    if word != EOS {
+      self.dict.compute_subwords(&[BOW, word, EOW].concat(), &mut ngrams, None);
+    }
+    // A label of a model without buckets has no row of its own.
+    ngrams.retain(|id| (*id as usize) < self.input.rows());
+    if ngrams.is_empty() {
+      return vec![0.0; self.args.dim as usize];
+    }
+    self.input.average_rows(&ngrams)
+  }
+
+  /// The probability of each label, like `Loss::computeOutput`.
+  fn compute_output(&self, hidden: &[f32]) -> Vec<f32> {
+    let mut output: Vec<f32> = (0..self.output.rows()).map(|i| self.output.dot_row(hidden, i)).collect();
+    if self.args.loss == LOSS_SOFTMAX {
+      let max = output.iter().fold(output[0], |max, v| v.max(max));
+      let mut z = 0.0f32;
+      for v in output.iter_mut() {
+        // fastText calls the `double` overload of `exp` here.
+        *v = ((*v - max) as f64).exp() as f32;
+        z += *v;
+      }
+      output.iter_mut().for_each(|v| *v /= z);
+    } else {
+      output.iter_mut().for_each(|v| *v = self.sigmoid(*v));
+    }
+    output
+  }
+
+  fn sigmoid(&self, x: f32) -> f32 {
+    if x < -MAX_SIGMOID {
+      0.0
+    } else if x > MAX_SIGMOID {
+      1.0
+    } else {
+      let i = ((x + MAX_SIGMOID) * SIGMOID_TABLE_SIZE as f32 / MAX_SIGMOID / 2.0) as usize;
+      self.sigmoid[i]
+    }
+  }
+
+  /// Walks the tree of the hierarchical softmax, like
+  /// `HierarchicalSoftmaxLoss::dfs`.
+  fn dfs(&self, heap: &mut TopK, threshold: f32, node: i32, score: f32, hidden: &[f32]) {
+    if score < std_log(threshold) || (heap.is_full() && score < heap.min()) {
+      return;
+    }
+    let n = self.tree[node as usize];
+    if n.left == -1 && n.right == -1 {
+      heap.push(score, node);
+      return;
+    }
+    let f = self.output.dot_row(hidden, (node - self.dict.nlabels) as usize);
+    let f = (1.0 / (1.0 + (-f).exp()) as f64) as f32;
+    self.dfs(heap, threshold, n.left, score + std_log((1.0 - f as f64) as f32), hidden);
+    self.dfs(heap, threshold, n.right, score + std_log(f), hidden);
+  }
+}
+
+/// `Vector::norm`.
+fn norm(vec: &[f32]) -> f32 {
+  vec.iter().fold(0.0f32, |sum, v| sum + v * v).sqrt()
+}
+
+/// `std::log(x + 1e-5)`, which fastText computes in `double`.
+fn std_log(x: f32) -> f32 {
+  (x as f64 + 1e-5).ln() as f32
+}
+
+/// `Vector::mul`, which takes its factor as a `real`.
+fn scale(vec: &mut [f32], a: f64) {
+  let a = a as f32;
+  vec.iter_mut().for_each(|v| *v *= a);
+}
+
+/// The `k` best `(score, label)` pairs, kept like `Loss::findKBest` does.
+///
+/// This is a min-heap managed like libstdc++'s `std::push_heap`,
+/// `std::pop_heap` and `std::sort_heap`, so that labels with the same score
+/// come out in the same order as with fastText.
+struct TopK {
+  k: usize,
+  heap: Vec<(f32, i32)>,
+}
+
+impl TopK {
+  fn new(k: usize) -> Self {
+    TopK { k, heap: Vec::with_capacity(k.min(CHUNK) + 1) }
+  }
+
+  fn is_full(&self) -> bool {
+    self.heap.len() == self.k
+  }
+
+  fn min(&self) -> f32 {
+    self.heap[0].0
+  }
+
+  fn push(&mut self, score: f32, label: i32) {
+    if self.is_full() && score < self.min() {
+      return;
+    }
+    self.heap.push((score, label));
+    let hole = self.heap.len() - 1;
+    sift_up(&mut self.heap, hole, 0, (score, label));
+    if self.heap.len() > self.k {
+      let last = self.heap.len() - 1;
+      pop_heap(&mut self.heap, last);
+      self.heap.pop();
+    }
+  }
+
+  /// The pairs by decreasing score.
+  fn into_sorted(mut self) -> Vec<(f32, i32)> {
+    for last in (1..self.heap.len()).rev() {
+      pop_heap(&mut self.heap, last);
+    }
+    self.heap
+  }
+}
+
+/// fastText's `comparePairs`, the "less than" of its heaps.
+fn heap_less(a: (f32, i32), b: (f32, i32)) -> bool {
+  a.0 > b.0
+}
+
+fn sift_up(heap: &mut [(f32, i32)], mut hole: usize, top: usize, value: (f32, i32)) {
+  while hole > top && heap_less(heap[(hole - 1) / 2], value) {
+    heap[hole] = heap[(hole - 1) / 2];
+    hole = (hole - 1) / 2;
+  }
+  heap[hole] = value;
+}
+
+/// Moves the top of `heap[..=last]` to `last`, keeping `heap[..last]` a heap.
+fn pop_heap(heap: &mut [(f32, i32)], last: usize) {
+  let value = heap[last];
+  heap[last] = heap[0];
+  // libstdc++'s `__adjust_heap`: move the hole down to a leaf, then sift the
+  // value up from there.
+  let len = last;
+  let mut hole = 0;
+  let mut child = 0;
+  while len > 0 && child < (len - 1) / 2 {
+    child = 2 * (child + 1);
+    if heap_less(heap[child], heap[child - 1]) {
+      child -= 1;
+    }
+    heap[hole] = heap[child];
+    hole = child;
+  }
+  if len.is_multiple_of(2) && len >= 2 && child == (len - 2) / 2 {
+    child = 2 * (child + 1);
+    heap[hole] = heap[child - 1];
+    hole = child - 1;
+  }
+  sift_up(heap, hole, 0, value);
+}
+
+impl Dictionary {
+  fn get_id(&self, word: &[u8]) -> i32 {
+    self.ids.get(word).copied().unwrap_or(-1)
+  }
+
+  fn label(&self, id: i32) -> String {
+    String::from_utf8_lossy(&self.words[(self.nwords + id) as usize].word).into_owned()
+  }
+
+  fn push_hash(&self, hashes: &mut Vec<i32>, id: i32) {
+    if self.pruneidx_size == 0 || id < 0 {
+      return;
+    }
+    let id = if self.pruneidx_size > 0 {
+      match self.pruneidx.get(&id) {
+        Some(id) => *id,
+        None => return,
+      }
+    } else {
+      id
+    };
+    hashes.push(self.nwords + id);
+  }
+
+  /// The ids of the character n-grams of `word`, which is wrapped in `<` and
+  /// `>`. A byte is part of the character before it when it continues a
+  /// UTF-8 sequence.
+  fn compute_subwords(&self, word: &[u8], ngrams: &mut Vec<i32>, mut substrings: Option<&mut Vec<Vec<u8>>>) {
+    if self.bucket <= 0 {
+      return;
+    }
+    for i in 0..word.len() {
+      if word[i] & 0xC0 == 0x80 {
+        continue;
+      }
+      let mut ngram = Vec::new();
+      let mut j = i;
+      let mut n = 1;
+      while j < word.len() && n <= self.maxn {
+        ngram.push(word[j]);
+        j += 1;
+        while j < word.len() && word[j] & 0xC0 == 0x80 {
+          ngram.push(word[j]);
+          j += 1;
+        }
+        if n >= self.minn && !(n == 1 && (i == 0 || j == word.len())) {
+          let len = ngrams.len();
+          self.push_hash(ngrams, (hash(&ngram) % self.bucket as u32) as i32);
+          // A pruned n-gram has no row, so its substring is dropped as well.
+          if let Some(substrings) = substrings.as_mut().filter(|_| ngrams.len() > len) {
+            substrings.push(ngram.clone());
+          }
+        }
+        n += 1;
+      }
+    }
+  }
+
+  fn add_word_ngrams(&self, line: &mut Vec<i32>, hashes: &[i32]) {
+    if self.bucket <= 0 {
+      return;
+    }
+    for i in 0..hashes.len() {
+      // The hashes are sign-extended, as fastText stores them as `int32_t`.
+      let mut h = hashes[i] as i64 as u64;
+      for hash in hashes.iter().take(i + self.word_ngrams.max(0) as usize).skip(i + 1) {
+        h = h.wrapping_mul(116049371).wrapping_add(*hash as i64 as u64);
+        self.push_hash(line, (h % self.bucket as u64) as i32);
+      }
+    }
+  }
+
+  /// The input rows of the first line of `text`, like `Dictionary::getLine`.
+  fn get_line(&self, text: &[u8]) -> Vec<i32> {
+    let mut words = Vec::new();
+    let mut hashes = Vec::new();
+    let mut tokens = Tokens { text, pos: 0 };
+    while let Some(token) = tokens.next() {
+      let h = hash(token);
+      let wid = self.get_id(token);
+      let is_label = if wid < 0 { token.starts_with(LABEL) } else { self.words[wid as usize].is_label };
+      if !is_label {
+        if wid < 0 {
+          if token != EOS {
+            self.compute_subwords(&[BOW, token, EOW].concat(), &mut words, None);
+          }
+        } else {
+          words.push(wid);
+          if self.maxn > 0 && token != EOS {
+            self.compute_subwords(&[BOW, token, EOW].concat(), &mut words, None);
+          }
+        }
+        hashes.push(h as i32);
+      }
+      if token == EOS {
+        break;
+      }
+    }
+    self.add_word_ngrams(&mut words, &hashes);
+    words
+  }
+}
+
+/// Splits text into words like `Dictionary::readWord`, where a newline is
+/// the `</s>` word.
+struct Tokens<'a> {
+  text: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+  fn next(&mut self) -> Option<&'a [u8]> {
+    let start = loop {
+      match self.text.get(self.pos) {
+        None => return None,
+        Some(b'\n') => {
+          self.pos += 1;
+          return Some(EOS);
+        }
+        Some(c) if is_space(*c) => self.pos += 1,
+        Some(_) => break self.pos,
+      }
+    };
+    // The newline ending a word is left for the next call.
+    while self.pos < self.text.len() && !is_space(self.text[self.pos]) {
+      self.pos += 1;
+    }
+    Some(&self.text[start..self.pos])
+  }
+}
+
+fn is_space(c: u8) -> bool {
+  matches!(c, b' ' | b'\n' | b'\r' | b'\t' | b'\x0b' | b'\x0c' | b'\0')
+}
+
+/// The FNV-1a hash of fastText, which sign-extends each byte.
+fn hash(bytes: &[u8]) -> u32 {
+  bytes.iter().fold(2166136261u32, |h, b| (h ^ (*b as i8 as u32)).wrapping_mul(16777619))
+}
+
+impl Matrix {
+  fn rows(&self) -> usize {
+    match self {
+      Matrix::Dense { rows, .. } => *rows,
+      Matrix::Quant(matrix) => matrix.rows,
+    }
+  }
+
+  fn cols(&self) -> usize {
+    match self {
+      Matrix::Dense { cols, .. } => *cols,
+      Matrix::Quant(matrix) => matrix.cols,
+    }
+  }
+
+  fn add_row_to_vector(&self, x: &mut [f32], i: usize) {
+    match self {
+      Matrix::Dense { cols, data, .. } => {
+        x.iter_mut().zip(&data[i * cols..(i + 1) * cols]).for_each(|(x, v)| *x += v);
+      }
+      Matrix::Quant(matrix) => matrix.pq.add_code(x, &matrix.codes, i, matrix.norm(i)),
+    }
+  }
+
+  fn dot_row(&self, vec: &[f32], i: usize) -> f32 {
+    match self {
+      Matrix::Dense { cols, data, .. } => {
+        data[i * cols..(i + 1) * cols].iter().zip(vec).fold(0.0, |d, (a, b)| d + a * b)
+      }
+      Matrix::Quant(matrix) => matrix.pq.mul_code(vec, &matrix.codes, i, matrix.norm(i)),
+    }
+  }
+
+  /// The mean of `rows`, like `Matrix::averageRowsToVector`.
+  fn average_rows(&self, rows: &[i32]) -> Vec<f32> {
+    let mut x = vec![0.0; self.cols()];
+    for row in rows {
+      self.add_row_to_vector(&mut x, *row as usize);
+    }
+    scale(&mut x, 1.0 / rows.len() as f64);
+    x
+  }
+}
+
+impl QuantMatrix {
+  fn norm(&self, i: usize) -> f32 {
+    match &self.norms {
+      Some((codes, npq)) => npq.centroids(0, codes[i])[0],
+      None => 1.0,
+    }
+  }
+}
+
+impl ProductQuantizer {
+  fn centroids(&self, m: usize, i: u8) -> &[f32] {
+    let start = if m == self.nsubq - 1 {
+      m * KSUB * self.dsub + i as usize * self.lastdsub
+    } else {
+      (m * KSUB + i as usize) * self.dsub
+    };
+    &self.centroids[start..]
+  }
+
+  fn sub_dim(&self, m: usize) -> usize {
+    if m == self.nsubq - 1 {
+      self.lastdsub
+    } else {
+      self.dsub
+    }
+  }
+
+  fn add_code(&self, x: &mut [f32], codes: &[u8], t: usize, alpha: f32) {
+    let code = &codes[self.nsubq * t..];
+    for m in 0..self.nsubq {
+      let c = self.centroids(m, code[m]);
+      for n in 0..self.sub_dim(m) {
+        x[m * self.dsub + n] += alpha * c[n];
+      }
+    }
+  }
+
+  fn mul_code(&self, x: &[f32], codes: &[u8], t: usize, alpha: f32) -> f32 {
+    let code = &codes[self.nsubq * t..];
+    let mut res = 0.0f32;
+    for m in 0..self.nsubq {
+      let c = self.centroids(m, code[m]);
+      for n in 0..self.sub_dim(m) {
+        res += x[m * self.dsub + n] * c[n];
+      }
+    }
+    res * alpha
+  }
+}
+
+/// Reads the little-endian values of a model file.
+struct ModelReader<R> {
+  reader: R,
+}
+
+impl<R: Read> ModelReader<R> {
+  fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    self.reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+  }
+
+  fn bool(&mut self) -> io::Result<bool> {
+    Ok(self.array::<1>()?[0] != 0)
+  }
+
+  fn i32(&mut self) -> io::Result<i32> {
+    Ok(i32::from_le_bytes(self.array()?))
+  }
+
+  fn i64(&mut self) -> io::Result<i64> {
+    Ok(i64::from_le_bytes(self.array()?))
+  }
+
+  fn f64(&mut self) -> io::Result<f64> {
+    Ok(f64::from_le_bytes(self.array()?))
+  }
+
+  fn bytes(&mut self, count: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let read = (&mut self.reader).take(count as u64).read_to_end(&mut bytes)?;
+    if read < count {
+      return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+  }
+
+  fn f32s(&mut self, count: usize) -> io::Result<Vec<f32>> {
+    let mut values = Vec::with_capacity(count.min(CHUNK));
+    let mut buffer = vec![0; 4 * count.min(CHUNK)];
+    while values.len() < count {
+      let n = (count - values.len()).min(CHUNK);
+      self.reader.read_exact(&mut buffer[..4 * n])?;
+      values.extend(buffer[..4 * n].chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())));
+    }
+    Ok(values)
+  }
+
+  /// A word of the dictionary, which ends with a NUL byte.
+  fn word(&mut self) -> io::Result<Vec<u8>> {
+    let mut word = Vec::new();
+    loop {
+      match self.array::<1>()?[0] {
+        0 => return Ok(word),
+        c => word.push(c),
+      }
+    }
+  }
+
+  fn dense_matrix(&mut self) -> io::Result<Matrix> {
+    let rows = size(self.i64()?)?;
+    let cols = size(self.i64()?)?;
+    let len = rows.checked_mul(cols).ok_or_else(|| invalid("the matrix is too large."))?;
+    Ok(Matrix::Dense { rows, cols, data: self.f32s(len)? })
+  }
+
+  fn quant_matrix(&mut self) -> io::Result<Matrix> {
+    let qnorm = self.bool()?;
+    let rows = size(self.i64()?)?;
+    let cols = size(self.i64()?)?;
+    let codesize = size(self.i32()? as i64)?;
+    let codes = self.bytes(codesize)?;
+    let pq = self.product_quantizer()?;
+    if pq.dim() != cols || codesize != rows * pq.nsubq {
+      return Err(invalid("the codes don't match the matrix."));
+    }
+    let norms = if qnorm {
+      let codes = self.bytes(rows)?;
+      Some((codes, self.product_quantizer()?))
+    } else {
+      None
+    };
+    Ok(Matrix::Quant(QuantMatrix { rows, cols, codes, pq, norms }))
+  }
+
+  fn product_quantizer(&mut self) -> io::Result<ProductQuantizer> {
+    let dim = size(self.i32()? as i64)?;
+    let nsubq = size(self.i32()? as i64)?;
+    let dsub = size(self.i32()? as i64)?;
+    let lastdsub = size(self.i32()? as i64)?;
+    if nsubq == 0 || (nsubq - 1) * dsub + lastdsub != dim {
+      return Err(invalid("the product quantizer is inconsistent."));
+    }
+    let centroids = self.f32s(dim * KSUB)?;
+    Ok(ProductQuantizer { nsubq, dsub, lastdsub, centroids })
+  }
+}
+
+impl ProductQuantizer {
+  fn dim(&self) -> usize {
+    (self.nsubq - 1) * self.dsub + self.lastdsub
+  }
+}
+
+fn invalid(message: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, format!("Invalid model file: {}", message))
+}
+
+fn size(value: i64) -> io::Result<usize> {
+  usize::try_from(value).map_err(|_| invalid(&format!("{} isn't a valid size.", value)))
+}
+
+/// Reads a model like `FastText::loadModel`.
+fn read_model<R: Read>(reader: &mut ModelReader<R>, name: &str) -> io::Result<NativeModel> {
+  let magic = reader.i32()?;
+  let version = reader.i32()?;
+  if magic != FILEFORMAT_MAGIC || version > VERSION {
+    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} has wrong file format!", name)));
+  }
+
+  let dim = reader.i32()?;
+  let _ws = reader.i32()?;
+  let _epoch = reader.i32()?;
+  let _min_count = reader.i32()?;
+  let _neg = reader.i32()?;
+  let word_ngrams = reader.i32()?;
+  let loss = reader.i32()?;
+  let model = reader.i32()?;
+  let bucket = reader.i32()?;
+  let minn = reader.i32()?;
+  let mut maxn = reader.i32()?;
+  let _lr_update_rate = reader.i32()?;
+  let _t = reader.f64()?;
+  if version == 11 && model == MODEL_SUPERVISED {
+    // Old supervised models don't use character n-grams.
+    maxn = 0;
+  }
+  if !(1..=4).contains(&loss) || !(1..=3).contains(&model) || dim < 0 || bucket < 0 {
+    return Err(invalid("the arguments are out of range."));
+  }
+  let args = StoredArgs { dim, loss, model };
+
+  let size_ = reader.i32()?;
+  let nwords = reader.i32()?;
+  let nlabels = reader.i32()?;
+  let _ntokens = reader.i64()?;
+  let pruneidx_size = reader.i64()?;
+  if size_ < 0 || nwords < 0 || nlabels < 0 || nwords.checked_add(nlabels) != Some(size_) {
+    return Err(invalid("the dictionary sizes don't add up."));
+  }
+  let mut words = Vec::new();
+  let mut ids = HashMap::new();
+  for i in 0..size_ {
+    let word = reader.word()?;
+    let count = reader.i64()?;
+    let is_label = reader.array::<1>()?[0] == 1;
+    ids.insert(word.clone(), i);
+    words.push(Entry { word, count, is_label });
+  }
+  let mut pruneidx = HashMap::new();
+  for _ in 0..pruneidx_size {
+    let first = reader.i32()?;
+    let second = reader.i32()?;
+    pruneidx.insert(first, second);
+  }
+  let dict = Dictionary {
+    nwords,
+    nlabels,
+    words,
+    ids,
+    pruneidx_size,
+    pruneidx,
+    minn,
+    maxn,
+    bucket,
+    word_ngrams,
+  };
+
+  let quant = reader.bool()?;
+  let input = if quant { reader.quant_matrix()? } else { reader.dense_matrix()? };
+  if !quant && pruneidx_size >= 0 {
+    return Err(invalid("the model was pruned without being quantized."));
+  }
+  let qout = reader.bool()?;
+  let output = if quant && qout { reader.quant_matrix()? } else { reader.dense_matrix()? };
+
+  // Every row a word or n-gram can map to must exist.
+  let ngrams = if pruneidx_size >= 0 { pruneidx_size } else { bucket as i64 };
+  if input.rows() as i64 != nwords as i64 + ngrams || input.cols() != dim as usize {
+    return Err(invalid("the input matrix doesn't match the dictionary."));
+  }
+  let outputs = if model == MODEL_SUPERVISED { nlabels } else { nwords };
+  if output.rows() != outputs as usize || output.cols() != dim as usize {
+    return Err(invalid("the output matrix doesn't match the dictionary."));
+  }
+
+  let sigmoid = (0..=SIGMOID_TABLE_SIZE)
+    .map(|i| {
+      let x = (i * 2 * MAX_SIGMOID as usize) as f32 / SIGMOID_TABLE_SIZE as f32 - MAX_SIGMOID;
+      (1.0 / (1.0 + (-x).exp() as f64)) as f32
+    })
+    .collect();
+  let tree = if loss == LOSS_HS && model == MODEL_SUPERVISED && nlabels > 0 {
+    let counts: Vec<i64> = dict.words.iter().filter(|e| e.is_label).map(|e| e.count).collect();
+    build_tree(&counts)
+  } else {
+    Vec::new()
+  };
+
+  Ok(NativeModel { args, dict, input, output, quant, sigmoid, tree, word_vectors: OnceLock::new() })
+}
+
+/// The Huffman tree of the hierarchical softmax, like
+/// `HierarchicalSoftmaxLoss::buildTree`.
+fn build_tree(counts: &[i64]) -> Vec<Node> {
+  let osz = counts.len();
+  let mut tree = vec![Node { left: -1, right: -1, count: 1e15 as i64 }; 2 * osz - 1];
+  for (node, count) in tree.iter_mut().zip(counts) {
+    node.count = *count;
+  }
+  let mut leaf = osz as i64 - 1;
+  let mut node = osz;
+  for i in osz..2 * osz - 1 {
+    let mut mini = [0; 2];
+    for m in mini.iter_mut() {
+      if leaf >= 0 && tree[leaf as usize].count < tree[node].count {
+        *m = leaf as usize;
+        leaf -= 1;
+      } else {
+        *m = node;
+        node += 1;
+      }
+    }
+    tree[i].left = mini[0] as i32;
+    tree[i].right = mini[1] as i32;
+    tree[i].count = tree[mini[0]].count + tree[mini[1]].count;
+  }
+  tree
+}
@@ -0,0 +1,8 @@
+/// Represents a single prediction from the fastText model.
+///
+/// Both `FastText` and the pure Rust `NativeModel` return it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Prediction {
+  pub probability: f32,
+  pub label: String,
+}
@@ -0,0 +1,134 @@
+use std::any::Any;
+use std::ffi::c_void;
+use std::ops::ControlFlow;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use flutter_rust_bridge::frb;
+
+/// How a training run ended.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TrainStatus {
+  /// Every epoch ran to the end.
+  Completed,
+  /// The training was stopped early by the progress callback or an [`AbortHandle`].
+  Aborted,
+}
+
+/// A progress report of a running training.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TrainProgress {
+  /// How much of the training is done, in `[0, 1]`.
+  pub progress: f32,
+  /// The average loss so far.
+  pub loss: f32,
+  /// The current learning rate.
+  pub lr: f64,
+  /// The number of words processed per second per thread.
+  pub words_per_sec: f64,
+}
+
+/// Stops a running training from another thread.
+///
+/// Every clone refers to the same flag, so a handle can be handed to a UI or a
+/// signal handler while the training runs elsewhere. The flag is cleared when
+/// a training finishes, which means aborting while nothing is running stops
+/// the next training as soon as it starts.
+///
+/// From Dart, get the handle before starting the training: the training
+/// holds the model, so `abortHandle()` would wait for it to finish.
+#[frb(opaque)]
+#[derive(Debug, Clone, Default)]
+pub struct AbortHandle {
+  aborted: Arc<AtomicBool>,
+}
+
+impl AbortHandle {
+  /// Asks the training to stop as soon as possible.
+  #[frb(sync)]
+  pub fn abort(&self) {
+    self.aborted.store(true, Ordering::SeqCst);
+  }
+
+  /// Whether an abort has been requested and not yet consumed by a training.
+  #[frb(sync)]
+  pub fn is_aborted(&self) -> bool {
+    self.aborted.load(Ordering::SeqCst)
+  }
+
+  pub(crate) fn reset(&self) {
+    self.aborted.store(false, Ordering::SeqCst);
+  }
+}
+
+/// Everything the C++ training threads need to report progress to Rust.
+pub(crate) struct ProgressState<F> {
+  callback: Mutex<F>,
+  abort: AbortHandle,
+  stopped: AtomicBool,
+  panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+impl<F> ProgressState<F>
+where
+  F: FnMut(f32, f32, f64, f64) -> ControlFlow<()> + Send,
+{
+  pub(crate) fn new(callback: F, abort: AbortHandle) -> Self {
+    ProgressState {
+      callback: Mutex::new(callback),
+      abort,
+      stopped: AtomicBool::new(false),
+      panic: Mutex::new(None),
+    }
+  }
+
+  /// Whether the training was asked to stop, by either the callback or the abort handle.
+  pub(crate) fn stopped(&self) -> bool {
+    self.stopped.load(Ordering::SeqCst)
+  }
+
+  /// Takes the payload of a panic raised by the callback, if any.
+  pub(crate) fn take_panic(&self) -> Option<Box<dyn Any + Send>> {
+    self.panic.lock().unwrap_or_else(|e| e.into_inner()).take()
+  }
+
+  /// The `fasttext_train_callback_t` handed to the C API along with `self` as user data.
+  ///
+  /// It is called concurrently from every training thread, so the callback
+  /// is serialized through a mutex. Panics are caught here, since unwinding
+  /// into C++ is not allowed, and turned into an abort.
+  pub(crate) unsafe extern "C" fn trampoline(
+    progress: f32,
+    loss: f32,
+    wst: f64,
+    lr: f64,
+    _eta: i64,
+    user_data: *mut c_void,
+  ) -> bool {
+    let state = unsafe { &*(user_data as *const Self) };
+    if state.stopped() {
+      return false;
+    }
+    if state.abort.is_aborted() {
+      state.stopped.store(true, Ordering::SeqCst);
+      return false;
+    }
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+      let mut callback = state.callback.lock().unwrap_or_else(|e| e.into_inner());
+      callback(progress, loss, lr, wst)
+    }));
+    match result {
+      Ok(ControlFlow::Continue(())) => true,
+      Ok(ControlFlow::Break(())) => {
+        state.stopped.store(true, Ordering::SeqCst);
+        false
+      }
+      Err(payload) => {
+        *state.panic.lock().unwrap_or_else(|e| e.into_inner()) = Some(payload);
+        state.stopped.store(true, Ordering::SeqCst);
+        false
+      }
+    }
+  }
+}
@@ -0,0 +1,307 @@
+use crate::api::error::FastTextError;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+#[cfg(feature = "cpp")]
+use std::io::Write;
+use std::sync::OnceLock;
+
+/// How many values `VectorModel::load_vec` allocates up front, whatever the
+/// header of the file says.
+const PREALLOCATED_VALUES: usize = 1 << 20;
+
+/// The file formats word vectors can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorFormat {
+  /// The `.vec` format of fastText: a `count dim` header line, then one line
+  /// per word with the word and its values separated by spaces.
+  Text,
+  /// The binary format of word2vec: the same header line, then each word
+  /// followed by a space, its values as little-endian `f32`s and a newline.
+  Word2VecBinary,
+  /// A NumPy `.npy` array of `f32`s of shape `(count, dim)`. The words aren't
+  /// part of it, the rows are in the order of [`FastText::words`].
+  ///
+  /// [`FastText::words`]: crate::api::fasttext::FastText::words
+  Npy,
+}
+
+/// Writes word vectors one row at a time, so that a whole vocabulary never
+/// needs to be held in memory.
+#[cfg(feature = "cpp")]
+pub(crate) struct VectorWriter<W: Write> {
+  writer: W,
+  format: VectorFormat,
+}
+
+#[cfg(feature = "cpp")]
+impl<W: Write> VectorWriter<W> {
+  /// Writes the header for `count` vectors of `dim` values.
+  pub(crate) fn new(mut writer: W, format: VectorFormat, count: usize, dim: usize) -> io::Result<Self> {
+    match format {
+      VectorFormat::Text | VectorFormat::Word2VecBinary => writeln!(writer, "{} {}", count, dim)?,
+      VectorFormat::Npy => writer.write_all(&npy_header(count, dim))?,
+    }
+    Ok(VectorWriter { writer, format })
+  }
+
+  pub(crate) fn write_row(&mut self, word: &str, vector: &[f32]) -> io::Result<()> {
+    match self.format {
+      VectorFormat::Text => {
+        self.writer.write_all(word.as_bytes())?;
+        for value in vector {
+          write!(self.writer, " {}", value)?;
+        }
+        self.writer.write_all(b"\n")
+      }
+      VectorFormat::Word2VecBinary => {
+        self.writer.write_all(word.as_bytes())?;
+        self.writer.write_all(b" ")?;
+        for value in vector {
+          self.writer.write_all(&value.to_le_bytes())?;
+        }
+        self.writer.write_all(b"\n")
+      }
+      VectorFormat::Npy => {
+        for value in vector {
+          self.writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+      }
+    }
+  }
+
+  pub(crate) fn finish(mut self) -> io::Result<()> {
+    self.writer.flush()
+  }
+}
+
+/// The header of a version 1.0 `.npy` file holding a C-ordered `f32` matrix.
+#[cfg(feature = "cpp")]
+fn npy_header(rows: usize, cols: usize) -> Vec<u8> {
+  let mut dict = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}", rows, cols);
+  // The magic string, version and length take 10 bytes, and the data must
+  // start on a multiple of 64 bytes. The dict ends with a newline.
+  let padding = (64 - (10 + dict.len() + 1) % 64) % 64;
+  dict.extend(std::iter::repeat_n(' ', padding));
+  dict.push('\n');
+
+  let mut header = b"\x93NUMPY\x01\x00".to_vec();
+  header.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+  header.extend_from_slice(dict.as_bytes());
+  header
+}
+
+/// Word vectors loaded from a `.vec` file, without the rest of a model.
+///
+/// This answers the same queries as a [`FastText`] instance, but only knows
+/// the words of the file: there are no subwords to build the vector of an
+/// unknown word from. The file can also be used as `pretrained_vectors` when
+/// training, with a `dim` of [`VectorModel::dim`]:
+///
+/// ```no_run
+/// # #[cfg(feature = "cpp")]
+/// # fn main() -> Result<(), fasttext::api::error::FastTextError> {
+/// # use fasttext::api::{args::TrainArgs, fasttext::FastText, vectors::VectorModel};
+/// let vectors = VectorModel::load_vec("cc.en.300.vec")?;
+/// let mut fasttext = FastText::new()?;
+/// fasttext.train_supervised(TrainArgs {
+///   input: "train.txt".to_string(),
+///   pretrained_vectors: vectors.path().to_string(),
+///   dim: vectors.dim(),
+///   ..Default::default()
+/// })?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "cpp"))]
+/// # fn main() {}
+/// ```
+///
+/// [`FastText`]: crate::api::fasttext::FastText
+pub struct VectorModel {
+  path: String,
+  dim: usize,
+  words: Vec<String>,
+  ids: HashMap<String, usize>,
+  vectors: Vec<f32>,
+  // Computed on the first neighbor query, like fastText does.
+  normalized: OnceLock<Vec<f32>>,
+}
+
+impl VectorModel {
+  /// Loads word vectors in the `.vec` text format.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The file to load, starting with a `count dim` header line.
+  pub fn load_vec(path: &str) -> Result<Self, FastTextError> {
+    let io_error = |e: io::Error| FastTextError::Io {
+      path: Some(path.to_string()),
+      message: format!("Failed to read the vectors: {}", e),
+    };
+    let invalid = |line: usize, reason: &str| FastTextError::InvalidModelFormat {
+      message: format!("{}:{}: {}", path, line, reason),
+    };
+
+    let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+    let mut buffer = Vec::new();
+    let mut read_line = |buffer: &mut Vec<u8>| -> Result<bool, FastTextError> {
+      buffer.clear();
+      Ok(reader.read_until(b'\n', buffer).map_err(io_error)? > 0)
+    };
+
+    if !read_line(&mut buffer)? {
+      return Err(invalid(1, "the header is missing"));
+    }
+    let header = String::from_utf8_lossy(&buffer);
+    let (count, dim) = match header.split_whitespace().collect::<Vec<_>>()[..] {
+      [count, dim] => match (count.parse::<usize>(), dim.parse::<usize>()) {
+        (Ok(count), Ok(dim)) => (count, dim),
+        _ => return Err(invalid(1, "the header isn't `count dim`")),
+      },
+      _ => return Err(invalid(1, "the header isn't `count dim`")),
+    };
+    let size = count.checked_mul(dim).ok_or_else(|| invalid(1, "the header is too large"))?;
+
+    // The header isn't trusted with the allocation: past a few megabytes, the
+    // vectors grow as they are read.
+    let words = count.min(PREALLOCATED_VALUES / dim.max(1));
+    let mut model = VectorModel {
+      path: path.to_string(),
+      dim,
+      words: Vec::with_capacity(words),
+      ids: HashMap::with_capacity(words),
+      vectors: Vec::with_capacity(size.min(PREALLOCATED_VALUES)),
+      normalized: OnceLock::new(),
+    };
+    for i in 0..count {
+      let line = i + 2;
+      if !read_line(&mut buffer)? {
+        return Err(invalid(line, &format!("expected {} vectors, found {}", count, line - 2)));
+      }
+      let text = std::str::from_utf8(&buffer).map_err(|_| invalid(line, "the word isn't valid UTF-8"))?;
+      let mut fields = text.split_whitespace();
+      let word = fields.next().ok_or_else(|| invalid(line, "the line is empty"))?;
+      let start = model.vectors.len();
+      for field in fields {
+        let value = field.parse::<f32>().map_err(|_| invalid(line, &format!("{} isn't a number", field)))?;
+        model.vectors.push(value);
+      }
+      if model.vectors.len() - start != dim {
+        return Err(invalid(line, &format!("expected {} values, found {}", dim, model.vectors.len() - start)));
+      }
+      // Like fastText's pretrained vectors, the last vector of a word wins. It
+      // replaces the earlier one, which would otherwise still be a neighbor.
+      if let Some(&id) = model.ids.get(word) {
+        model.vectors.copy_within(start.., id * dim);
+        model.vectors.truncate(start);
+      } else {
+        model.ids.insert(word.to_string(), model.words.len());
+        model.words.push(word.to_string());
+      }
+    }
+    Ok(model)
+  }
+
+  /// The file the vectors were loaded from.
+  pub fn path(&self) -> &str {
+    &self.path
+  }
+
+  /// The dimension of the vectors.
+  pub fn dim(&self) -> i32 {
+    self.dim as i32
+  }
+
+  /// The words of the file, in its order. A word listed more than once only
+  /// appears where it comes first.
+  pub fn words(&self) -> &[String] {
+    &self.words
+  }
+
+  /// Get the vector of a word.
+  ///
+  /// Like a fastText model without subwords, an unknown word has a vector of
+  /// zeros.
+  ///
+  /// # Arguments
+  ///
+  /// * `word` - The word to get the vector for.
+  pub fn get_word_vector(&self, word: &str) -> Result<Vec<f32>, FastTextError> {
+    Ok(self.ids.get(word).map_or_else(|| vec![0.0; self.dim], |id| self.row(&self.vectors, *id).to_vec()))
+  }
+
+  /// Nearest neighbors for a given word, by cosine similarity.
+  ///
+  /// # Arguments
+  ///
+  /// * `word` - The word to find the neighbors of.
+  /// * `k` - The number of neighbors to return.
+  pub fn get_nn(&self, word: &str, k: i32) -> Result<Vec<(f32, String)>, FastTextError> {
+    let query = self.get_word_vector(word)?;
+    Ok(self.nearest(&query, k, &[word]))
+  }
+
+  /// Finds the words `d` for which `word_a - word_b + word_c = d`, e.g.
+  /// `berlin - germany + france = paris`.
+  ///
+  /// # Arguments
+  ///
+  /// * `k` - The number of analogies to return.
+  /// * `word_a`, `word_b`, `word_c` - The words of the analogy.
+  pub fn get_analogies(
+    &self,
+    k: i32,
+    word_a: &str,
+    word_b: &str,
+    word_c: &str,
+  ) -> Result<Vec<(f32, String)>, FastTextError> {
+    let mut query = vec![0.0; self.dim];
+    for (word, sign) in [(word_a, 1.0), (word_b, -1.0), (word_c, 1.0)] {
+      let vector = self.get_word_vector(word)?;
+      let scale = sign / (norm(&vector) + 1e-8);
+      for (q, v) in query.iter_mut().zip(&vector) {
+        *q += scale * v;
+      }
+    }
+    Ok(self.nearest(&query, k, &[word_a, word_b, word_c]))
+  }
+
+  fn row<'a>(&self, matrix: &'a [f32], id: usize) -> &'a [f32] {
+    &matrix[id * self.dim..(id + 1) * self.dim]
+  }
+
+  /// The `k` words most similar to `query`, leaving out `banned`, like
+  /// `FastText::getNN` does.
+  fn nearest(&self, query: &[f32], k: i32, banned: &[&str]) -> Vec<(f32, String)> {
+    let normalized = self.normalized.get_or_init(|| {
+      let mut normalized = self.vectors.clone();
+      for row in normalized.chunks_mut(self.dim.max(1)) {
+        let n = norm(row);
+        if n > 0.0 {
+          row.iter_mut().for_each(|v| *v /= n);
+        }
+      }
+      normalized
+    });
+    let mut query_norm = norm(query);
+    if query_norm.abs() < 1e-8 {
+      query_norm = 1.0;
+    }
+
+    let mut similarities: Vec<(f32, usize)> = (0..self.words.len())
+      .filter(|id| !banned.contains(&self.words[*id].as_str()))
+      .map(|id| {
+        let dot: f32 = self.row(normalized, id).iter().zip(query).map(|(a, b)| a * b).sum();
+        (dot / query_norm, id)
+      })
+      .collect();
+    similarities.sort_by(|a, b| b.0.total_cmp(&a.0));
+    similarities.truncate(k.max(0) as usize);
+    similarities.into_iter().map(|(similarity, id)| (similarity, self.words[id].clone())).collect()
+  }
+}
+
+fn norm(vector: &[f32]) -> f32 {
+  vector.iter().map(|v| v * v).sum::<f32>().sqrt()
+}
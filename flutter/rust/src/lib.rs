@@ -1,2 +1,3 @@
 pub mod api;
+#[cfg(feature = "cpp")]
 mod frb_generated;
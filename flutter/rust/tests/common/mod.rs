@@ -0,0 +1,167 @@
+// Models trained at test time, so the suite doesn't depend on downloads.
+//
+// Every model is trained deterministically on a generated corpus, and cached
+// under `target/` next to the corpus. The cache directory is named after a
+// hash of the corpora and the arguments, so changing either retrains the
+// models instead of reusing stale files.
+#![allow(dead_code)]
+
+use fasttext::api::args::{QuantizeArgs, TrainArgs};
+use fasttext::api::fasttext::FastText;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub const BAKING: [&str; 8] = ["bake", "baking", "oven", "dish", "cake", "muffin", "flour", "sugar"];
+pub const BREAD: [&str; 8] = ["bread", "banana", "loaf", "yeast", "dough", "rise", "crust", "knead"];
+pub const GRILLING: [&str; 8] = ["grill", "charcoal", "steak", "smoke", "barbecue", "burger", "skewer", "coals"];
+pub const FRYING: [&str; 8] = ["fry", "oil", "pan", "crispy", "batter", "wok", "deep", "fritter"];
+
+pub struct Fixtures {
+    /// The labelled corpus the supervised models are trained on.
+    pub corpus: PathBuf,
+    /// The corpus without its labels, which the skipgram model is trained on.
+    pub text: PathBuf,
+    /// A supervised softmax model with word n-grams and subwords.
+    pub supervised: PathBuf,
+    /// The supervised model, quantized with a cutoff and retraining.
+    pub quantized: PathBuf,
+    /// A skipgram model of the corpus without its labels.
+    pub skipgram: PathBuf,
+}
+
+/// Returns the fixtures, building the ones that aren't cached yet.
+pub fn fixtures() -> &'static Fixtures {
+    static FIXTURES: OnceLock<Fixtures> = OnceLock::new();
+    FIXTURES.get_or_init(build)
+}
+
+/// A line of the corpus: one or two labels and the words of their topics.
+fn corpus_line(i: usize) -> String {
+    let topics: [(&str, &[&str]); 4] = [
+        ("baking", &BAKING),
+        ("bread", &BREAD),
+        ("grilling", &GRILLING),
+        ("frying", &FRYING),
+    ];
+    let (label, words) = topics[i % topics.len()];
+    let mut line = format!("__label__{}", label);
+    let mut text: Vec<&str> = (0..6).map(|j| words[(i * 7 + j * 3) % words.len()]).collect();
+    // Banana bread is baked, so some bread questions are about baking too.
+    if label == "bread" && i.is_multiple_of(3) {
+        line.push_str(" __label__baking");
+        text.extend([BAKING[i % BAKING.len()], "bake"]);
+    }
+    for word in text {
+        line.push(' ');
+        line.push_str(word);
+    }
+    line
+}
+
+fn corpus() -> String {
+    (0..400).map(|i| corpus_line(i) + "\n").collect()
+}
+
+fn text(corpus: &str) -> String {
+    corpus
+        .lines()
+        .map(|line| {
+            let words: Vec<&str> = line.split(' ').filter(|word| !word.starts_with("__label__")).collect();
+            words.join(" ") + "\n"
+        })
+        .collect()
+}
+
+fn train_args() -> TrainArgs {
+    TrainArgs {
+        dim: 16,
+        epoch: 10,
+        lr: 0.5,
+        word_ngrams: 2,
+        minn: 2,
+        maxn: 4,
+        bucket: 2000,
+        seed: 1,
+        verbose: 0,
+        ..Default::default()
+    }
+    .deterministic(true)
+}
+
+fn skipgram_args() -> TrainArgs {
+    TrainArgs {
+        dim: 10,
+        epoch: 5,
+        min_count: 1,
+        bucket: 1000,
+        seed: 1,
+        verbose: 0,
+        ..TrainArgs::unsupervised()
+    }
+    .deterministic(true)
+}
+
+fn quantize_args() -> QuantizeArgs {
+    QuantizeArgs {
+        cutoff: 500,
+        retrain: true,
+        qnorm: true,
+        thread: 1,
+        verbose: 0,
+        ..Default::default()
+    }
+}
+
+fn cache_dir(corpus: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    corpus.hash(&mut hasher);
+    text(corpus).hash(&mut hasher);
+    format!("{:?} {:?} {:?}", train_args(), skipgram_args(), quantize_args()).hash(&mut hasher);
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("fixtures-{:016x}", hasher.finish()))
+}
+
+/// Writes `path` through a temporary file, so that concurrent test binaries
+/// never see it half written.
+fn write_atomically(path: &Path, write: impl FnOnce(&Path)) {
+    let temp = path.with_extension(format!("tmp-{}", std::process::id()));
+    write(&temp);
+    std::fs::rename(&temp, path).expect("Failed to move fixture into place");
+}
+
+fn build() -> Fixtures {
+    let corpus = corpus();
+    let dir = cache_dir(&corpus);
+    std::fs::create_dir_all(&dir).expect("Failed to create fixture directory");
+    let fixtures = Fixtures {
+        corpus: dir.join("corpus.txt"),
+        text: dir.join("text.txt"),
+        supervised: dir.join("supervised.bin"),
+        quantized: dir.join("quantized.ftz"),
+        skipgram: dir.join("skipgram.bin"),
+    };
+    let input = fixtures.corpus.to_str().unwrap().to_string();
+    if !fixtures.corpus.exists() {
+        write_atomically(&fixtures.corpus, |path| std::fs::write(path, &corpus).expect("Failed to write corpus"));
+    }
+    if !fixtures.text.exists() {
+        write_atomically(&fixtures.text, |path| std::fs::write(path, text(&corpus)).expect("Failed to write text"));
+    }
+
+    if !fixtures.supervised.exists() || !fixtures.quantized.exists() {
+        let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+        fasttext.train_supervised(TrainArgs { input: input.clone(), ..train_args() }).unwrap();
+        write_atomically(&fixtures.supervised, |path| fasttext.save_model(path.to_str().unwrap()).unwrap());
+        fasttext.quantize(QuantizeArgs { input: input.clone(), ..quantize_args() }).unwrap();
+        write_atomically(&fixtures.quantized, |path| fasttext.save_model(path.to_str().unwrap()).unwrap());
+    }
+
+    if !fixtures.skipgram.exists() {
+        let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+        let input = fixtures.text.to_str().unwrap().to_string();
+        fasttext.train_skipgram(TrainArgs { input, ..skipgram_args() }).unwrap();
+        write_atomically(&fixtures.skipgram, |path| fasttext.save_model(path.to_str().unwrap()).unwrap());
+    }
+    fixtures
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "cpp")]
+
+use fasttext::api::args::{Args, Loss, ModelName, TrainArgs};
+use fasttext::api::autotune::{AutotuneConfig, AutotuneMetric};
+use fasttext::api::error::FastTextError;
+
+#[test]
+fn test_args_defaults() {
+    let args = Args::new();
+    assert_eq!(ModelName::Skipgram, args.model());
+    assert_eq!(Loss::Ns, args.loss());
+    assert_eq!(100, args.dim());
+    assert_eq!("__label__", args.label());
+    assert!(!args.qout());
+}
+
+#[test]
+fn test_args_builder() {
+    let args = Args::new()
+        .with_input("train.txt")
+        .unwrap()
+        .with_model(ModelName::Supervised)
+        .with_loss(Loss::Ova)
+        .with_epoch(25)
+        .with_lr(0.5)
+        .with_cutoff(1000)
+        .with_qnorm(true);
+    assert_eq!("train.txt", args.input());
+    assert_eq!(ModelName::Supervised, args.model());
+    assert_eq!(Loss::Ova, args.loss());
+    assert_eq!(25, args.epoch());
+    assert_eq!(0.5, args.lr());
+    assert_eq!(1000, args.cutoff());
+    assert!(args.qnorm());
+}
+
+#[test]
+fn test_args_builder_nul_in_string() {
+    assert_eq!(
+        FastTextError::NulInInput { arg: "label".to_string() },
+        Args::new().with_label("__la\0bel__").unwrap_err()
+    );
+}
+
+#[test]
+fn test_args_from_cli() {
+    let args = Args::from_cli(&[
+        "supervised", "-input", "train.txt", "-output", "model", "-epoch", "25", "-loss", "ova",
+        "-wordNgrams", "2", "-qout",
+    ])
+    .unwrap();
+    assert_eq!(ModelName::Supervised, args.model());
+    assert_eq!(Loss::Ova, args.loss());
+    assert_eq!("train.txt", args.input());
+    assert_eq!("model", args.output());
+    assert_eq!(25, args.epoch());
+    assert_eq!(2, args.word_ngrams());
+    assert_eq!(1, args.min_count());
+    assert!(args.qout());
+}
+
+#[test]
+fn test_args_from_cli_errors() {
+    assert!(Args::from_cli(&[]).is_err());
+    assert_eq!(
+        "Unknown argument: -nope",
+        Args::from_cli(&["supervised", "-input", "a", "-output", "b", "-nope", "1"])
+            .unwrap_err()
+            .to_string()
+    );
+    assert_eq!(
+        "-epoch is missing an argument",
+        Args::from_cli(&["supervised", "-input", "a", "-output", "b", "-epoch"])
+            .unwrap_err()
+            .to_string()
+    );
+    assert!(matches!(
+        Args::from_cli(&["supervised", "-input", "a", "-output", "b", "-loss", "l2"]),
+        Err(FastTextError::InvalidArgument { .. })
+    ));
+    assert!(Args::from_cli(&["supervised", "-input", "a", "-output", "b", "-dim", "ten"]).is_err());
+    // fastText reads floats with `std::stof`, which exits when they don't fit.
+    for lr in ["1e400", "1e39", "-1e39", "1e-40", "1e-400"] {
+        assert_eq!(
+            format!("Invalid value for -lr: {}", lr),
+            Args::from_cli(&["supervised", "-input", "a", "-output", "b", "-lr", lr]).unwrap_err().to_string()
+        );
+    }
+    for lr in ["0", "0.0e-400", "1e-37", "3e38", "inf", "-infinity", "nan"] {
+        assert!(Args::from_cli(&["supervised", "-input", "a", "-output", "b", "-lr", lr]).is_ok(), "{}", lr);
+    }
+    assert_eq!(
+        "Empty input or output path.",
+        Args::from_cli(&["supervised", "-input", "a"]).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_autotune_config_metric() {
+    let metric = |metric| AutotuneConfig { metric, ..Default::default() }.to_args("train.txt").unwrap().autotune_metric();
+    assert_eq!("f1", metric(AutotuneMetric::F1));
+    assert_eq!("f1:__label__a", metric(AutotuneMetric::F1Label("__label__a".to_string())));
+    assert_eq!(
+        "precisionAtRecall:30",
+        metric(AutotuneMetric::PrecisionAtRecall { recall: 0.3, label: None })
+    );
+    assert_eq!(
+        "recallAtPrecision:12.5:__label__a",
+        metric(AutotuneMetric::RecallAtPrecision { precision: 0.125, label: Some("__label__a".to_string()) })
+    );
+
+    // The percentage stays short and reads back as the same value.
+    for value in [0.0, 0.01, 0.07, 0.1, 0.29, 0.3, 0.33, 0.57, 0.99, 1.0, 0.123456] {
+        let flag = metric(AutotuneMetric::PrecisionAtRecall { recall: value, label: None });
+        let percent = flag.strip_prefix("precisionAtRecall:").unwrap();
+        assert!(percent.len() <= 8, "{}", flag);
+        assert_eq!(value, percent.parse::<f64>().unwrap() / 100.0, "{}", flag);
+    }
+}
+
+#[test]
+fn test_train_args_training_hash_is_stable() {
+    let input = std::env::temp_dir().join(format!("fasttext-rs-training-hash-{}.txt", std::process::id()));
+    std::fs::write(&input, "__label__baking oven flour\n__label__grilling charcoal steak\n").unwrap();
+    let args = TrainArgs { input: input.to_str().unwrap().to_string(), ..Default::default() };
+    let hash = args.training_hash(ModelName::Supervised).unwrap();
+
+    // Saved hashes must keep matching, so the scheme can't depend on how the
+    // arguments happen to be formatted.
+    assert_eq!("81ec9d8d7b984f13", hash);
+    assert_eq!(hash, TrainArgs { thread: 1, verbose: 0, ..args.clone() }.training_hash(ModelName::Supervised).unwrap());
+    let label = TrainArgs { label: "__label__b".to_string(), ..args.clone() };
+    assert_ne!(hash, label.training_hash(ModelName::Supervised).unwrap());
+    assert_ne!(hash, TrainArgs { lr: 0.2, ..args }.training_hash(ModelName::Supervised).unwrap());
+}
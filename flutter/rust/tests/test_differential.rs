@@ -0,0 +1,219 @@
+#![cfg(all(feature = "cpp", feature = "pure-rust"))]
+
+// Trains tiny models on generated corpora and checks that the C++ and the
+// native code paths agree on every query, so no expected value depends on
+// the outcome of a particular training run.
+
+mod common;
+
+use common::{fixtures, BAKING, GRILLING};
+use fasttext::api::args::{Loss, TrainArgs};
+use fasttext::api::error::FastTextError;
+use fasttext::api::fasttext::FastText;
+use fasttext::api::native::NativeModel;
+use std::path::PathBuf;
+
+/// How far apart two floats may be, relative to the larger one.
+const TOLERANCE: f32 = 1e-5;
+
+const TEXTS: [&str; 9] = [
+    "oven flour yeast",
+    "charcoal steak smoke",
+    "Which baking dish is best to bake a banana bread ?",
+    "ovens with unknown wörds and ünïcode",
+    "bake grill\ncharcoal steak on a second line",
+    "  \t leading whitespace\x0bvertical\x0ctabs\rreturns",
+    "__label__baking is a label, not a word",
+    "",
+    "\n",
+];
+
+/// The queries both code paths answer, with the results in a comparable form.
+trait Backend {
+    fn predict(&self, text: &str, k: i32, threshold: f32) -> Result<Vec<(f32, String)>, FastTextError>;
+    fn word_vector(&self, word: &str) -> Vec<f32>;
+    fn sentence_vector(&self, text: &str) -> Vec<f32>;
+    fn nn(&self, word: &str, k: i32) -> Vec<(f32, String)>;
+    fn subwords(&self, word: &str) -> Vec<(String, i32)>;
+}
+
+impl Backend for FastText {
+    fn predict(&self, text: &str, k: i32, threshold: f32) -> Result<Vec<(f32, String)>, FastTextError> {
+        let predictions = FastText::predict(self, text, k, threshold)?;
+        Ok(predictions.into_iter().map(|p| (p.probability, p.label)).collect())
+    }
+
+    fn word_vector(&self, word: &str) -> Vec<f32> {
+        self.get_word_vector(word).unwrap()
+    }
+
+    fn sentence_vector(&self, text: &str) -> Vec<f32> {
+        self.get_sentence_vector(text).unwrap()
+    }
+
+    fn nn(&self, word: &str, k: i32) -> Vec<(f32, String)> {
+        self.get_nn(word, k).unwrap()
+    }
+
+    fn subwords(&self, word: &str) -> Vec<(String, i32)> {
+        self.get_subwords(word).unwrap()
+    }
+}
+
+impl Backend for NativeModel {
+    fn predict(&self, text: &str, k: i32, threshold: f32) -> Result<Vec<(f32, String)>, FastTextError> {
+        let predictions = NativeModel::predict(self, text, k, threshold)?;
+        Ok(predictions.into_iter().map(|p| (p.probability, p.label)).collect())
+    }
+
+    fn word_vector(&self, word: &str) -> Vec<f32> {
+        self.get_word_vector(word).unwrap()
+    }
+
+    fn sentence_vector(&self, text: &str) -> Vec<f32> {
+        self.get_sentence_vector(text).unwrap()
+    }
+
+    fn nn(&self, word: &str, k: i32) -> Vec<(f32, String)> {
+        self.get_nn(word, k).unwrap()
+    }
+
+    fn subwords(&self, word: &str) -> Vec<(String, i32)> {
+        self.get_subwords(word).unwrap()
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fasttext-rs-differential-{}-{}", name, std::process::id()))
+}
+
+fn assert_close(expected: &[f32], actual: &[f32], context: &str) {
+    assert_eq!(expected.len(), actual.len(), "{}", context);
+    for (i, (e, a)) in expected.iter().zip(actual).enumerate() {
+        let scale = e.abs().max(a.abs()).max(1.0);
+        assert!((e - a).abs() <= TOLERANCE * scale, "{}: {} != {} at {}", context, e, a, i);
+    }
+}
+
+/// Compares results made of a float and a string, which have to be in the
+/// same order.
+fn assert_close_ranked(expected: &[(f32, String)], actual: &[(f32, String)], context: &str) {
+    let labels = |results: &[(f32, String)]| results.iter().map(|(_, label)| label.clone()).collect::<Vec<_>>();
+    let scores = |results: &[(f32, String)]| results.iter().map(|(score, _)| *score).collect::<Vec<_>>();
+    assert_eq!(labels(expected), labels(actual), "{}", context);
+    assert_close(&scores(expected), &scores(actual), context);
+}
+
+/// Checks every query of `words` and `TEXTS` against both code paths.
+fn assert_agree(cpp: &dyn Backend, native: &dyn Backend, words: &[&str], supervised: bool) {
+    // fastText asserts that a label isn't looked up as a word.
+    let words: Vec<&str> = TEXTS
+        .iter()
+        .flat_map(|text| text.split_whitespace())
+        .chain(words.iter().copied())
+        .chain(["</s>", "", "ovens", "xyz"])
+        .filter(|word| !word.starts_with("__label__"))
+        .collect();
+    for word in &words {
+        assert_close(&cpp.word_vector(word), &native.word_vector(word), &format!("word vector of {:?}", word));
+        assert_eq!(cpp.subwords(word), native.subwords(word), "subwords of {:?}", word);
+    }
+    // fastText reads the top of an empty heap when `k` is 0.
+    for word in words.iter().take(12) {
+        for k in [1, 5, 50] {
+            assert_close_ranked(&cpp.nn(word, k), &native.nn(word, k), &format!("nn of {:?} k={}", word, k));
+        }
+    }
+    for text in TEXTS {
+        let context = format!("sentence vector of {:?}", text);
+        assert_close(&cpp.sentence_vector(text), &native.sentence_vector(text), &context);
+        for (k, threshold) in [(1, 0.0), (2, 0.0), (-1, 0.0), (-1, 0.3)] {
+            let context = format!("predict {:?} k={} threshold={}", text, k, threshold);
+            match (cpp.predict(text, k, threshold), native.predict(text, k, threshold)) {
+                (Ok(expected), Ok(actual)) => {
+                    assert!(supervised, "{}", context);
+                    assert_close_ranked(&expected, &actual, &context);
+                }
+                (expected, actual) => {
+                    assert!(!supervised, "{}", context);
+                    assert_eq!(expected.err(), actual.err(), "{}", context);
+                }
+            }
+        }
+    }
+}
+
+fn train_supervised(loss: Loss) -> FastText {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_supervised(TrainArgs {
+            input: fixtures().corpus.to_str().unwrap().to_string(),
+            dim: 16,
+            epoch: 10,
+            lr: 0.5,
+            loss,
+            word_ngrams: 2,
+            minn: 2,
+            maxn: 4,
+            bucket: 2000,
+            thread: 1,
+            verbose: 0,
+            ..Default::default()
+        })
+        .unwrap();
+    fasttext
+}
+
+/// Saves `fasttext` and loads the file back without the C++.
+fn load_native(fasttext: &mut FastText, name: &str) -> NativeModel {
+    let path = temp_path(name);
+    fasttext.save_model(path.to_str().unwrap()).unwrap();
+    NativeModel::load_model(path.to_str().unwrap()).unwrap()
+}
+
+#[test]
+fn test_differential_supervised_losses() {
+    for (name, loss) in [
+        ("softmax", Loss::Softmax),
+        ("hs", Loss::Hs),
+        ("ova", Loss::Ova),
+        ("ns", Loss::Ns),
+    ] {
+        let mut fasttext = train_supervised(loss);
+        let native = load_native(&mut fasttext, &format!("{}.bin", name));
+        assert!(!native.is_quant());
+        assert_eq!(fasttext.get_dimension().unwrap(), native.get_dimension());
+        assert_agree(&fasttext, &native, &BAKING, true);
+    }
+}
+
+#[test]
+fn test_differential_quantized() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model(fixtures().quantized.to_str().unwrap()).unwrap();
+    let native = NativeModel::load_model(fixtures().quantized.to_str().unwrap()).unwrap();
+    assert!(native.is_quant());
+    assert_agree(&fasttext, &native, &GRILLING, true);
+}
+
+#[test]
+fn test_differential_pretrained() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model("tests/fixtures/lid.176.ftz").unwrap();
+    let native = NativeModel::load_model("tests/fixtures/lid.176.ftz").unwrap();
+    assert_agree(
+        &fasttext,
+        &native,
+        &["Bonjour à tous", "Guten Morgen, wie geht's?", "これは日本語です", "King", "king"],
+        true,
+    );
+}
+
+#[test]
+fn test_differential_skipgram() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model(fixtures().skipgram.to_str().unwrap()).unwrap();
+    let native = NativeModel::load_model(fixtures().skipgram.to_str().unwrap()).unwrap();
+    assert_agree(&fasttext, &native, &GRILLING, false);
+    assert_eq!(FastTextError::NotSupervised, native.predict("oven", 1, 0.0).unwrap_err());
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "cpp")]
+
+use fasttext::api::error::FastTextError;
+use fasttext::api::fasttext::FastText;
+use std::io::{self, Read};
+
+const TEST_SET: &str = "\
+__label__en The weather is really nice today and we are going to the beach
+__label__en I would like to order a cup of coffee and a croissant please
+__label__fr Le chat dort sur le canapé depuis ce matin
+__label__fr Nous allons au cinéma ce soir avec des amis
+__label__de Ich habe heute keine Zeit, weil ich arbeiten muss
+__label__es El perro de mi vecino ladra toda la noche
+";
+
+fn load_lid_model() -> FastText {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model("tests/fixtures/lid.176.ftz").unwrap();
+    fasttext
+}
+
+#[test]
+fn test_fasttext_evaluate() {
+    let path = std::env::temp_dir().join(format!("fasttext-rs-evaluate-{}.txt", std::process::id()));
+    std::fs::write(&path, TEST_SET).unwrap();
+    let fasttext = load_lid_model();
+    let report = fasttext.evaluate(path.to_str().unwrap(), 1, 0.0).unwrap();
+
+    assert_eq!(6, report.nexamples);
+    assert_eq!(1.0, report.precision);
+    assert_eq!(1.0, report.recall);
+    assert_eq!(1.0, report.f1_score);
+    assert_eq!(176, report.labels.len());
+
+    let en = report.label("__label__en").unwrap();
+    assert_eq!(2, en.gold);
+    assert_eq!(2, en.predicted);
+    assert_eq!(1.0, en.precision);
+    assert_eq!(1.0, en.recall);
+    assert_eq!(2, en.score_vs_true.len());
+    assert!(en.score_vs_true.iter().all(|(score, gold)| *gold && *score > 0.0 && *score <= 1.0));
+
+    let it = report.label("__label__it").unwrap();
+    assert_eq!(0, it.gold);
+    assert!(it.precision.is_nan());
+    assert!(report.label("__label__klingon").is_none());
+}
+
+#[test]
+fn test_fasttext_evaluate_counts_missed_labels() {
+    let fasttext = load_lid_model();
+    let report = fasttext
+        .evaluate_reader("__label__fr The weather is really nice today\n".as_bytes(), 1, 0.0)
+        .unwrap();
+
+    assert_eq!(1, report.nexamples);
+    assert_eq!(0.0, report.precision);
+    let fr = report.label("__label__fr").unwrap();
+    assert_eq!(1, fr.gold);
+    assert_eq!(0, fr.predicted);
+    assert_eq!(vec![(-1.0, true)], fr.score_vs_true);
+    let en = report.label("__label__en").unwrap();
+    assert_eq!(1, en.predicted);
+    assert!(!en.score_vs_true[0].1);
+}
+
+#[test]
+fn test_fasttext_evaluate_reader_matches_file() {
+    let path = std::env::temp_dir().join(format!("fasttext-rs-evaluate-reader-{}.txt", std::process::id()));
+    std::fs::write(&path, TEST_SET).unwrap();
+    let fasttext = load_lid_model();
+
+    let from_file = fasttext.evaluate(path.to_str().unwrap(), 2, 0.01).unwrap();
+    // A tiny buffer makes the C++ side pull the data over several reads.
+    let reader = io::BufReader::with_capacity(7, TEST_SET.as_bytes()).take(u64::MAX);
+    let from_reader = fasttext.evaluate_reader(reader, 2, 0.01).unwrap();
+    // Labels that never show up have NaN metrics, which aren't equal to themselves.
+    assert_eq!(format!("{:?}", from_file), format!("{:?}", from_reader));
+}
+
+struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::other("connection reset"))
+    }
+}
+
+#[test]
+fn test_fasttext_evaluate_errors() {
+    let fasttext = load_lid_model();
+    let err = fasttext.evaluate("tests/fixtures/does-not-exist.txt", 1, 0.0).unwrap_err();
+    assert!(matches!(err, FastTextError::Io { path: Some(_), .. }));
+    let err = fasttext.evaluate_reader(FailingReader, 1, 0.0).unwrap_err();
+    assert!(matches!(err, FastTextError::Io { path: None, .. }));
+    assert!(err.to_string().contains("connection reset"));
+
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(
+        FastTextError::ModelNotLoaded,
+        untrained.evaluate_reader(TEST_SET.as_bytes(), 1, 0.0).unwrap_err()
+    );
+}
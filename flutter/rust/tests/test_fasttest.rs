@@ -1,27 +1,64 @@
+#![cfg(feature = "cpp")]
+
+mod common;
+
+use common::{fixtures, GRILLING};
+use fasttext::api::error::FastTextError;
 use fasttext::api::fasttext::FastText;
 
 #[test]
 fn test_fasttext_load_model() {
     let mut fasttext = FastText::new().expect("Failed to create FastText instance");
     assert!(fasttext
-      .load_model("tests/fixtures/cooking.model.bin")
+      .load_model(fixtures().supervised.to_str().unwrap())
       .is_ok());
 }
 
-// TODO: Need to catch the error in C/C++ side before crossing the FFI boundary
 #[test]
 fn test_fasttext_load_invalid_model() {
     let mut fasttext = FastText::new().expect("Failed to create FastText instance");
     let result = fasttext.load_model("tests/fixtures/invalid.model.bin");
-    assert!(result.is_err());
-    assert_eq!("tests/fixtures/invalid.model.bin has wrong file format!", result.unwrap_err());
+    assert_eq!(
+        FastTextError::InvalidModelFormat {
+            message: "tests/fixtures/invalid.model.bin has wrong file format!".to_string()
+        },
+        result.unwrap_err()
+    );
+}
+
+#[test]
+fn test_fasttext_load_missing_model() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let err = fasttext.load_model("tests/fixtures/does-not-exist.bin").unwrap_err();
+    assert!(matches!(
+        err,
+        FastTextError::Io { path: Some(ref path), .. } if path == "tests/fixtures/does-not-exist.bin"
+    ));
+}
+
+#[test]
+fn test_fasttext_model_not_loaded() {
+    let fasttext = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, fasttext.predict("banana bread", 1, 0.0).unwrap_err());
+    assert_eq!(FastTextError::ModelNotLoaded, fasttext.get_dimension().unwrap_err());
+    assert_eq!(FastTextError::ModelNotLoaded, fasttext.get_word_vector("banana").unwrap_err());
+    assert_eq!(FastTextError::ModelNotLoaded, fasttext.get_nn("banana", 1).unwrap_err());
+}
+
+#[test]
+fn test_fasttext_nul_in_input() {
+    let fasttext = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(
+        FastTextError::NulInInput { arg: "text".to_string() },
+        fasttext.predict("banana\0bread", 1, 0.0).unwrap_err()
+    );
 }
 
 #[test]
 fn test_fasttext_predict() {
     let mut fasttext = FastText::new().expect("Failed to create FastText instance");
     fasttext
-        .load_model("tests/fixtures/cooking.model.bin")
+        .load_model(fixtures().supervised.to_str().unwrap())
         .unwrap();
     let preds = fasttext
         .predict("Which baking dish is best to bake a banana bread ?", 2, 0.0)
@@ -33,9 +70,8 @@ fn test_fasttext_predict() {
 
 #[test]
 fn test_fasttext_predict_from_buffer() {
-    let model_path_str = "tests/fixtures/cooking.model.bin";
     let mut fasttext = FastText::new().expect("Failed to create FastText instance");
-    let model_data = std::fs::read(model_path_str).expect("Failed to read model file");
+    let model_data = std::fs::read(&fixtures().supervised).expect("Failed to read model file");
     fasttext
         .load_model_from_buffer(&model_data)
         .unwrap();
@@ -47,18 +83,25 @@ fn test_fasttext_predict_from_buffer() {
     assert_eq!("__label__bread", &preds[1].label);
 }
 
+#[test]
+fn test_fasttext_predict_quantized() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .load_model(fixtures().quantized.to_str().unwrap())
+        .unwrap();
+    let preds = fasttext
+        .predict("Which baking dish is best to bake a banana bread ?", 2, 0.0)
+        .unwrap();
+    assert_eq!(2, preds.len());
+    assert_eq!("__label__baking", &preds[0].label);
+    assert_eq!("__label__bread", &preds[1].label);
+}
+
 #[test]
 fn test_fasttext_predict_on_language() {
-    // The language identification model can be downloaded from:
+    // The language identification model is checked in, it comes from:
     // https://fasttext.cc/docs/en/language-identification.html
     let model_path_str = "tests/fixtures/lid.176.ftz";
-    if !std::path::Path::new(model_path_str).exists() {
-        eprintln!(
-            "Skipping test: model file not found at '{}'. Please download it.",
-            model_path_str
-        );
-        return;
-    }
 
     // No more `unsafe` block! The RAII wrapper handles all unsafe operations.
     let mut ft = FastText::new().expect("Failed to create FastText instance");
@@ -97,6 +140,23 @@ fn test_fasttext_nn() {
     assert_eq!("won", &neighbors[2].1);
 }
 
+#[test]
+fn test_fasttext_nn_skipgram() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+      .load_model(fixtures().skipgram.to_str().unwrap())
+      .unwrap();
+    let neighbors = fasttext
+      .get_nn("charcoal", 3)
+      .unwrap();
+    assert_eq!(3, neighbors.len());
+    assert!(neighbors.windows(2).all(|pair| pair[0].0 >= pair[1].0), "{:?}", neighbors);
+    let vocabulary: Vec<&str> = [common::BAKING, common::BREAD, common::GRILLING, common::FRYING].concat();
+    for (_, word) in &neighbors {
+        assert!(word != "charcoal" && vocabulary.contains(&word.as_str()), "{:?}", neighbors);
+    }
+}
+
 #[test]
 fn test_fasttext_get_analogies() {
     let mut fasttext = FastText::new().expect("Failed to create FastText instance");
@@ -130,10 +190,43 @@ fn test_fasttext_get_subword_id() {
     fasttext
       .load_model("tests/fixtures/lid.176.ftz")
       .unwrap();
+    // The model is quantized with a cutoff, which pruned this subword.
     let id = fasttext
       .get_subword_id("king")
       .unwrap();
-    assert_eq!(743833, id);
+    assert_eq!(-1, id);
+    let (subword, id) = fasttext.get_subwords("king").unwrap().pop().unwrap();
+    assert_eq!(id, fasttext.get_subword_id(&subword).unwrap());
+}
+
+#[test]
+fn test_fasttext_get_subwords_quantized() {
+    let mut supervised = FastText::new().expect("Failed to create FastText instance");
+    supervised
+      .load_model(fixtures().supervised.to_str().unwrap())
+      .unwrap();
+    let mut quantized = FastText::new().expect("Failed to create FastText instance");
+    quantized
+      .load_model(fixtures().quantized.to_str().unwrap())
+      .unwrap();
+    let nwords = quantized.nwords().unwrap();
+
+    // The cutoff prunes the n-grams that training left small, the ones kept
+    // are paired with their own rows.
+    let mut pruned = 0;
+    for word in GRILLING.iter().chain(&["xylophone", "jukebox", "quartz"]) {
+        let subwords = quantized.get_subwords(word).unwrap();
+        for (subword, id) in subwords.iter().filter(|(_, id)| *id >= nwords) {
+            assert_eq!(quantized.get_subword_id(subword).unwrap(), *id, "{subword}");
+        }
+        for (subword, _) in supervised.get_subwords(word).unwrap() {
+            if !subwords.iter().any(|(s, _)| *s == subword) {
+                assert_eq!(-1, quantized.get_subword_id(&subword).unwrap(), "{subword}");
+                pruned += 1;
+            }
+        }
+    }
+    assert!(pruned > 0);
 }
 
 #[test]
@@ -157,14 +250,12 @@ fn test_fasttext_get_word_vector() {
     let vec = fasttext
       .get_word_vector("king")
       .unwrap();
-    assert_eq!(
-        vec![
-            -0.23979793, -0.44772798, -0.5446826, 0.34108365, -0.25004652, -0.23136835, 0.1491945,
-            0.36125875, -0.26137757, -0.18064976, 0.38271922, 0.67140853, 0.2586546, 0.41923445,
-            -0.21165611, 0.49381053
-        ],
-        vec
-    );
+    assert_eq!(16, vec.len());
+    assert!(vec.iter().all(|v| v.is_finite()) && vec.iter().any(|v| *v != 0.0));
+    assert_eq!(vec, fasttext.get_word_vector("king").unwrap());
+    // The model has no word n-grams, so a one word sentence averages the same rows.
+    assert_eq!(vec, fasttext.get_sentence_vector("king").unwrap());
+    assert_ne!(vec, fasttext.get_word_vector("queen").unwrap());
 }
 
 #[test]
@@ -176,12 +267,139 @@ fn test_fasttext_get_sentence_vector() {
     let vec = fasttext
       .get_sentence_vector("king is to queen as man is to ?")
       .unwrap();
-    assert_eq!(
-        vec![
-            0.38501006, 0.15357634, -1.5447631, 0.6713988, -1.1916713, -0.23329756, 1.1790949,
-            0.22402689, -0.7763773, -1.5958408, 0.7044491, -0.20077848, 1.0720185, 0.27003786,
-            -0.36773762, 1.2104399
-        ],
-        vec
-    );
+    assert_eq!(16, vec.len());
+    assert!(vec.iter().all(|v| v.is_finite()));
+    // Without word n-grams the order of the words doesn't matter.
+    let shuffled = fasttext
+      .get_sentence_vector("? to is man as queen to is king")
+      .unwrap();
+    for (a, b) in vec.iter().zip(&shuffled) {
+        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+    }
+    assert_ne!(vec, fasttext.get_word_vector("king").unwrap());
+}
+
+#[test]
+fn test_fasttext_save_model_to_vec() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model("tests/fixtures/lid.176.ftz").unwrap();
+    let bytes = fasttext.save_model_to_vec().unwrap();
+    assert_eq!(fasttext.model_size().unwrap(), bytes.len() as u64);
+
+    // The same bytes as a saved file, and they load back to the same model.
+    let path = std::env::temp_dir().join(format!("fasttext-rs-save-to-vec-{}.ftz", std::process::id()));
+    fasttext.save_model(path.to_str().unwrap()).unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    let mut loaded = FastText::new().expect("Failed to create FastText instance");
+    loaded.load_model_from_buffer(&bytes).unwrap();
+    let text = "Which baking dish is best to bake a banana bread?";
+    assert_eq!(fasttext.predict(text, 3, 0.0).unwrap(), loaded.predict(text, 3, 0.0).unwrap());
+
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.save_model_to_vec().unwrap_err());
+}
+
+/// Accepts `limit` bytes, then fails or panics.
+struct FailingWriter {
+    limit: usize,
+    panic: bool,
+}
+
+impl std::io::Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.len() > self.limit {
+            if self.panic {
+                panic!("writer panicked");
+            }
+            return Err(std::io::Error::other("disk full"));
+        }
+        self.limit -= buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_fasttext_save_model_to_writer_errors() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model("tests/fixtures/lid.176.ftz").unwrap();
+
+    let err = fasttext.save_model_to_writer(FailingWriter { limit: 100_000, panic: false }).unwrap_err();
+    assert!(matches!(err, FastTextError::Io { path: None, ref message } if message.contains("disk full")));
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        fasttext.save_model_to_writer(FailingWriter { limit: 0, panic: true })
+    }));
+    assert_eq!(&"writer panicked", panic.unwrap_err().downcast_ref::<&str>().unwrap());
+}
+
+#[test]
+fn test_fasttext_load_model_from_reader() {
+    let file = std::fs::File::open("tests/fixtures/lid.176.ftz").unwrap();
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model_from_reader(std::io::BufReader::new(file)).unwrap();
+
+    let mut expected = FastText::new().expect("Failed to create FastText instance");
+    expected.load_model("tests/fixtures/lid.176.ftz").unwrap();
+    let text = "Which baking dish is best to bake a banana bread?";
+    assert_eq!(expected.predict(text, 3, 0.0).unwrap(), fasttext.predict(text, 3, 0.0).unwrap());
+    assert_eq!(expected.save_model_to_vec().unwrap(), fasttext.save_model_to_vec().unwrap());
+}
+
+#[test]
+fn test_fasttext_load_model_from_reader_errors() {
+    let bytes = std::fs::read("tests/fixtures/lid.176.ftz").unwrap();
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model_from_reader(bytes.as_slice()).unwrap();
+    let text = "Which baking dish is best to bake a banana bread?";
+    let expected = fasttext.predict(text, 1, 0.0).unwrap();
+
+    let invalid = std::fs::read("tests/fixtures/invalid.model.bin").unwrap();
+    assert!(matches!(
+        fasttext.load_model_from_reader(invalid.as_slice()),
+        Err(FastTextError::InvalidModelFormat { .. })
+    ));
+    assert!(matches!(
+        fasttext.load_model_from_reader(&bytes[..bytes.len() / 2]),
+        Err(FastTextError::InvalidModelFormat { .. })
+    ));
+    let failing = std::io::Read::chain(&bytes[..1000], FailingReader);
+    let err = fasttext.load_model_from_reader(failing).unwrap_err();
+    assert!(matches!(err, FastTextError::Io { path: None, ref message } if message.contains("connection reset")));
+
+    // The model loaded before is still there.
+    assert_eq!(expected, fasttext.predict(text, 1, 0.0).unwrap());
+}
+
+#[test]
+fn test_fasttext_load_model_unknown_loss_or_model() {
+    let bytes = std::fs::read("tests/fixtures/lid.176.ftz").unwrap();
+    let path = std::env::temp_dir().join(format!("fasttext-rs-unknown-enum-{}.bin", std::process::id()));
+    // The loss and the model follow the magic, the version and six other ints.
+    for (offset, name) in [(32, "loss"), (36, "model")] {
+        for value in [0i32, 5, -1] {
+            let mut corrupted = bytes.clone();
+            corrupted[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            std::fs::write(&path, &corrupted).unwrap();
+            let expected = FastTextError::InvalidModelFormat { message: format!("Invalid model file: unknown {}.", name) };
+
+            let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+            assert_eq!(expected, fasttext.load_model(path.to_str().unwrap()).unwrap_err());
+            assert_eq!(expected, fasttext.load_model_from_buffer(&corrupted).unwrap_err());
+            assert_eq!(expected, fasttext.load_model_from_reader(corrupted.as_slice()).unwrap_err());
+            assert_eq!(expected, fasttext.load_model_mmap(path.to_str().unwrap()).unwrap_err());
+            assert_eq!(FastTextError::ModelNotLoaded, fasttext.get_dimension().unwrap_err());
+        }
+    }
+}
+
+struct FailingReader;
+
+impl std::io::Read for FailingReader {
+    fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset"))
+    }
 }
@@ -0,0 +1,65 @@
+#![cfg(feature = "pure-rust")]
+
+use fasttext::api::error::FastTextError;
+use fasttext::api::native::NativeModel;
+
+#[test]
+fn test_native_predict() {
+    let native = NativeModel::load_model("tests/fixtures/lid.176.ftz").unwrap();
+    assert!(native.is_quant());
+    assert_eq!(16, native.get_dimension());
+    let preds = native.predict("Bonjour à tous, comment allez-vous ?", 2, 0.0).unwrap();
+    assert_eq!(2, preds.len());
+    assert_eq!("__label__fr", &preds[0].label);
+    assert!(preds[0].probability > preds[1].probability);
+    assert_eq!(16, native.get_word_vector("bonjour").unwrap().len());
+    assert_eq!(16, native.get_sentence_vector("bonjour").unwrap().len());
+}
+
+#[test]
+fn test_native_nn_and_subwords() {
+    let native = NativeModel::load_model("tests/fixtures/lid.176.ftz").unwrap();
+    let neighbors = native.get_nn("king", 3).unwrap();
+    assert_eq!(3, neighbors.len());
+    assert!(neighbors.iter().all(|(_, word)| word != "king"));
+    assert!(neighbors.windows(2).all(|pair| pair[0].0 >= pair[1].0));
+    assert!(native.get_nn("king", 0).unwrap().is_empty());
+
+    let subwords = native.get_subwords("king").unwrap();
+    assert_eq!(("king".to_string(), 1567), subwords[0]);
+    assert!(subwords[1..].iter().all(|(subword, _)| "<king>".contains(subword.as_str())));
+    assert!(native.get_subwords("</s>").unwrap().iter().all(|(subword, _)| subword == "</s>"));
+}
+
+#[test]
+fn test_native_predict_errors() {
+    let native = NativeModel::load_model("tests/fixtures/lid.176.ftz").unwrap();
+    assert!(matches!(native.predict("hello", 0, 0.0), Err(FastTextError::InvalidArgument { .. })));
+    // Like fastText, an empty text has no predictions before `k` is checked.
+    assert_eq!(Vec::<fasttext::api::native::Prediction>::new(), native.predict("", 0, 0.0).unwrap());
+    assert!(native.predict("hello", 1, 1.1).unwrap().is_empty());
+}
+
+#[test]
+fn test_native_load_errors() {
+    let result = NativeModel::load_model("tests/fixtures/does-not-exist.bin");
+    assert!(matches!(
+        result,
+        Err(FastTextError::Io { path: Some(ref path), .. }) if path == "tests/fixtures/does-not-exist.bin"
+    ));
+    assert!(matches!(
+        NativeModel::load_model("tests/fixtures/invalid.model.bin"),
+        Err(FastTextError::InvalidModelFormat { .. })
+    ));
+
+    let bytes = std::fs::read("tests/fixtures/lid.176.ftz").unwrap();
+    for len in [0, 6, 100, bytes.len() / 2, bytes.len() - 1] {
+        assert_eq!(
+            Some(FastTextError::InvalidModelFormat { message: "Model from reader is truncated!".to_string() }),
+            NativeModel::load_model_from_reader(&bytes[..len]).err(),
+            "{}",
+            len
+        );
+    }
+    assert!(NativeModel::load_model_from_reader(bytes.as_slice()).is_ok());
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "cpp")]
+
+use fasttext::api::error::FastTextError;
+use fasttext::api::fasttext::FastText;
+
+const TEXTS: [&str; 6] = [
+    "The weather is really nice today and we are going to the beach",
+    "Le chat dort sur le canapé depuis ce matin",
+    "Ich habe heute keine Zeit, weil ich arbeiten muss",
+    "El perro de mi vecino ladra toda la noche",
+    "",
+    "Nous allons au cinéma ce soir avec des amis",
+];
+
+fn load_lid_model() -> FastText {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model("tests/fixtures/lid.176.ftz").unwrap();
+    fasttext
+}
+
+#[test]
+fn test_fasttext_predict_batch() {
+    let fasttext = load_lid_model();
+    let batch = fasttext.predict_batch(&TEXTS, 2, 0.0).unwrap();
+
+    assert_eq!(TEXTS.len(), batch.len());
+    for (text, predictions) in TEXTS.iter().zip(&batch) {
+        assert_eq!(&fasttext.predict(text, 2, 0.0).unwrap(), predictions);
+    }
+    assert_eq!("__label__en", &batch[0][0].label);
+    assert_eq!("__label__fr", &batch[1][0].label);
+    assert!(batch[4].is_empty());
+}
+
+#[test]
+fn test_fasttext_predict_batch_with_threads() {
+    let fasttext = load_lid_model();
+    let texts: Vec<&str> = TEXTS.iter().cycle().take(100).copied().collect();
+    let expected = fasttext.predict_batch(&texts, 1, 0.0).unwrap();
+
+    // More threads than texts is fine too.
+    for threads in [2, 3, 8, 200] {
+        assert_eq!(expected, fasttext.predict_batch_with_threads(&texts, 1, 0.0, threads).unwrap());
+    }
+    assert!(fasttext.predict_batch_with_threads(&[], 1, 0.0, 4).unwrap().is_empty());
+}
+
+#[test]
+fn test_fasttext_predict_batch_with_too_many_threads() {
+    let fasttext = load_lid_model();
+    let texts: Vec<&str> = TEXTS.iter().cycle().take(20_000).copied().collect();
+    let expected = fasttext.predict_batch(&texts, 1, 0.0).unwrap();
+
+    // Capped to the number of cores, instead of failing to start the threads.
+    for threads in [10_000, i32::MAX] {
+        assert_eq!(expected, fasttext.predict_batch_with_threads(&texts, 1, 0.0, threads).unwrap());
+    }
+}
+
+#[test]
+fn test_fasttext_predict_batch_errors() {
+    let fasttext = load_lid_model();
+    assert_eq!(
+        FastTextError::NulInInput { arg: "texts".to_string() },
+        fasttext.predict_batch(&["fine", "not\0fine"], 1, 0.0).unwrap_err()
+    );
+    assert!(matches!(
+        fasttext.predict_batch_with_threads(&TEXTS, 1, 0.0, 0),
+        Err(FastTextError::InvalidArgument { .. })
+    ));
+
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.predict_batch(&TEXTS, 1, 0.0).unwrap_err());
+}
@@ -0,0 +1,989 @@
+#![cfg(feature = "cpp")]
+
+use fasttext::api::args::{Args, Loss, ModelArgs, ModelName, QuantizeArgs, TrainArgs};
+use fasttext::api::autotune::{AutotuneConfig, AutotuneMetric, Hyperparameter};
+use fasttext::api::error::FastTextError;
+use fasttext::api::fasttext::{FastText, NgramKind};
+use fasttext::api::train::{TrainProgress, TrainStatus};
+use fasttext::api::vectors::{VectorFormat, VectorModel};
+use std::borrow::Cow;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Writes a small labelled corpus where each class has its own vocabulary.
+fn write_supervised_corpus(name: &str) -> PathBuf {
+    let baking = ["bake", "oven", "flour", "dough", "cake", "muffin", "yeast", "loaf"];
+    let grilling = ["grill", "charcoal", "steak", "smoke", "barbecue", "burger", "skewer", "coals"];
+    let mut corpus = String::new();
+    for i in 0..200 {
+        let (label, words) = if i % 2 == 0 { ("baking", &baking) } else { ("grilling", &grilling) };
+        let line: Vec<&str> = (0..6).map(|j| words[(i * 7 + j * 3) % words.len()]).collect();
+        corpus.push_str(&format!("__label__{} {}\n", label, line.join(" ")));
+    }
+    let path = std::env::temp_dir().join(format!("fasttext-rs-{}-{}.txt", name, std::process::id()));
+    std::fs::write(&path, corpus).expect("Failed to write corpus");
+    path
+}
+
+/// Writes a small unlabelled corpus with two clearly separated topics.
+fn write_unsupervised_corpus(name: &str) -> PathBuf {
+    let topics = [
+        ["bake", "oven", "flour", "dough", "cake", "muffin", "yeast", "loaf"],
+        ["grill", "charcoal", "steak", "smoke", "barbecue", "burger", "skewer", "coals"],
+    ];
+    let mut corpus = String::new();
+    for i in 0..400 {
+        let words = &topics[i % 2];
+        let line: Vec<&str> = (0..10).map(|j| words[(i * 5 + j * 3) % words.len()]).collect();
+        corpus.push_str(&line.join(" "));
+        corpus.push('\n');
+    }
+    let path = std::env::temp_dir().join(format!("fasttext-rs-{}-{}.txt", name, std::process::id()));
+    std::fs::write(&path, corpus).expect("Failed to write corpus");
+    path
+}
+
+#[test]
+fn test_fasttext_train_supervised() {
+    let input = write_supervised_corpus("train_supervised");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_supervised(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 10,
+            epoch: 20,
+            lr: 0.5,
+            thread: 1,
+            verbose: 0,
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(10, fasttext.get_dimension().unwrap());
+    let preds = fasttext.predict("oven flour yeast", 1, 0.0).unwrap();
+    assert_eq!("__label__baking", &preds[0].label);
+    let preds = fasttext.predict("charcoal steak smoke", 1, 0.0).unwrap();
+    assert_eq!("__label__grilling", &preds[0].label);
+}
+
+#[test]
+fn test_fasttext_train_supervised_missing_input() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let result = fasttext.train_supervised(TrainArgs {
+        input: "tests/fixtures/does-not-exist.txt".to_string(),
+        verbose: 0,
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(FastTextError::Io { .. })));
+}
+
+#[test]
+fn test_fasttext_train_from_cli_args() {
+    let input = write_supervised_corpus("train_from_cli_args");
+    let args = Args::from_cli(&[
+        "supervised", "-input", input.to_str().unwrap(), "-output", "unused", "-dim", "10",
+        "-epoch", "20", "-lr", "0.5", "-thread", "1", "-verbose", "0",
+    ])
+    .unwrap();
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.train(&args).unwrap();
+
+    let preds = fasttext.predict("oven flour yeast", 1, 0.0).unwrap();
+    assert_eq!("__label__baking", &preds[0].label);
+}
+
+#[test]
+fn test_fasttext_train_skipgram() {
+    let input = write_unsupervised_corpus("train_skipgram");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_skipgram(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 10,
+            epoch: 10,
+            min_count: 1,
+            bucket: 10000,
+            minn: 2,
+            maxn: 4,
+            neg: 3,
+            lr_update_rate: 50,
+            thread: 1,
+            verbose: 0,
+            ..TrainArgs::unsupervised()
+        })
+        .unwrap();
+
+    assert_eq!(10, fasttext.get_dimension().unwrap());
+    let neighbors = fasttext.get_nn("oven", 3).unwrap();
+    assert_eq!(3, neighbors.len());
+    // Out of vocabulary words still get a vector from their character n-grams.
+    let vec = fasttext.get_word_vector("ovens").unwrap();
+    assert!(vec.iter().any(|v| *v != 0.0));
+}
+
+#[test]
+fn test_fasttext_train_cbow() {
+    let input = write_unsupervised_corpus("train_cbow");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_cbow(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 10,
+            epoch: 10,
+            min_count: 1,
+            bucket: 10000,
+            thread: 1,
+            verbose: 0,
+            ..TrainArgs::unsupervised()
+        })
+        .unwrap();
+
+    assert_eq!(10, fasttext.get_dimension().unwrap());
+    let vec = fasttext.get_word_vector("charcoal").unwrap();
+    assert_eq!(10, vec.len());
+    assert!(vec.iter().any(|v| *v != 0.0));
+    // Word vectors models can't classify.
+    assert_eq!(FastTextError::NotSupervised, fasttext.predict("charcoal", 1, 0.0).unwrap_err());
+}
+
+#[test]
+fn test_fasttext_get_subwords() {
+    let input = write_unsupervised_corpus("subwords");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_cbow(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 10,
+            epoch: 1,
+            min_count: 1,
+            bucket: 10000,
+            thread: 1,
+            verbose: 0,
+            ..TrainArgs::unsupervised()
+        })
+        .unwrap();
+
+    let subwords = fasttext.get_subwords("oven").unwrap();
+    let expected =
+        ["oven", "<ov", "<ove", "<oven", "<oven>", "ove", "oven", "oven>", "ven", "ven>", "en>"];
+    assert_eq!(expected.to_vec(), subwords.iter().map(|(s, _)| s.as_str()).collect::<Vec<_>>());
+    assert_eq!(fasttext.get_word_id("oven").unwrap(), subwords[0].1);
+    for (subword, id) in &subwords[1..] {
+        assert_eq!(fasttext.get_subword_id(subword).unwrap(), *id);
+    }
+
+    // A misspelling shares most of its n-grams with the word, but not the word itself.
+    let nwords = fasttext.nwords().unwrap();
+    let misspelled = fasttext.get_subwords("ovem").unwrap();
+    assert_eq!("<ov", misspelled[0].0);
+    assert!(misspelled.iter().all(|(_, id)| *id >= nwords));
+    assert_eq!(3, misspelled.iter().filter(|s| subwords[1..].contains(s)).count());
+}
+
+#[test]
+fn test_fasttext_ngram_vectors() {
+    let input = write_unsupervised_corpus("ngram_vectors");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_skipgram(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 8,
+            epoch: 1,
+            min_count: 1,
+            bucket: 10000,
+            thread: 1,
+            verbose: 0,
+            ..TrainArgs::unsupervised()
+        })
+        .unwrap();
+
+    let ngrams = fasttext.ngram_vectors("flour").unwrap();
+    let subwords = fasttext.get_subwords("flour").unwrap();
+    assert_eq!(
+        subwords.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>(),
+        ngrams.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>()
+    );
+    assert!(ngrams.iter().all(|(_, v)| v.len() == 8));
+
+    // The word vector is the average of its n-gram vectors.
+    let word_vector = fasttext.get_word_vector("flour").unwrap();
+    for (i, value) in word_vector.iter().enumerate() {
+        let mean = ngrams.iter().map(|(_, v)| v[i]).sum::<f32>() / ngrams.len() as f32;
+        assert!((value - mean).abs() < 1e-6, "{} != {}", value, mean);
+    }
+
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.ngram_vectors("flour").unwrap_err());
+}
+
+#[test]
+fn test_fasttext_matrices() {
+    let input = write_unsupervised_corpus("matrices");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_skipgram(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 8,
+            epoch: 1,
+            min_count: 1,
+            bucket: 1000,
+            thread: 1,
+            verbose: 0,
+            ..TrainArgs::unsupervised()
+        })
+        .unwrap();
+
+    let nwords = fasttext.nwords().unwrap() as usize;
+    let input_matrix = fasttext.input_matrix().unwrap();
+    assert_eq!((nwords + 1000, 8), (input_matrix.rows, input_matrix.cols));
+    assert_eq!(input_matrix.rows * input_matrix.cols, input_matrix.data.len());
+    let output_matrix = fasttext.output_matrix().unwrap();
+    assert_eq!((nwords, 8), (output_matrix.rows, output_matrix.cols));
+
+    // The word vector is the average of the rows of its subwords.
+    let subwords = fasttext.get_subwords("dough").unwrap();
+    let word_vector = fasttext.get_word_vector("dough").unwrap();
+    for (i, value) in word_vector.iter().enumerate() {
+        let sum: f32 = subwords.iter().map(|(_, id)| input_matrix.row(*id as usize)[i]).sum();
+        assert!((value - sum / subwords.len() as f32).abs() < 1e-6);
+    }
+
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.input_matrix().unwrap_err());
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.output_matrix().unwrap_err());
+}
+
+#[test]
+fn test_fasttext_label_vectors() {
+    let (fasttext, _) = train_small_classifier("label_vectors");
+    let label_vectors = fasttext.label_vectors().unwrap();
+    let labels: Vec<String> = fasttext.labels().unwrap().into_iter().map(|(label, _)| label).collect();
+    assert_eq!(labels, label_vectors.iter().map(|(label, _)| label.clone()).collect::<Vec<_>>());
+    let output_matrix = fasttext.output_matrix().unwrap();
+    for (i, (_, vector)) in label_vectors.iter().enumerate() {
+        assert_eq!(output_matrix.row(i), vector.as_slice());
+    }
+
+    // The same rows, with the 5 significant digits fastText writes.
+    let path = std::env::temp_dir().join(format!("fasttext-rs-save-output-{}.vec", std::process::id()));
+    fasttext.save_output(path.to_str().unwrap()).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(Some("2 16"), lines.next());
+    for (line, (label, vector)) in lines.zip(&label_vectors) {
+        let mut fields = line.split_whitespace();
+        assert_eq!(Some(label.as_str()), fields.next());
+        for (field, value) in fields.zip(vector) {
+            let saved: f32 = field.parse().unwrap();
+            assert!((saved - value).abs() <= 1e-4 * value.abs().max(1.0));
+        }
+    }
+
+    let missing = "tests/fixtures/missing-dir/output.vec";
+    assert!(matches!(
+        fasttext.save_output(missing),
+        Err(FastTextError::Io { path: Some(p), .. }) if p == missing
+    ));
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.label_vectors().unwrap_err());
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.save_output(path.to_str().unwrap()).unwrap_err());
+}
+
+#[test]
+fn test_fasttext_label_vectors_errors() {
+    let input = write_supervised_corpus("label_vectors_hs");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_supervised(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 4,
+            epoch: 1,
+            loss: Loss::Hs,
+            thread: 1,
+            verbose: 0,
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(matches!(fasttext.label_vectors(), Err(FastTextError::InvalidArgument { .. })));
+
+    let input = write_unsupervised_corpus("label_vectors_cbow");
+    fasttext
+        .train_cbow(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 4,
+            epoch: 1,
+            min_count: 1,
+            thread: 1,
+            verbose: 0,
+            ..TrainArgs::unsupervised()
+        })
+        .unwrap();
+    assert_eq!(FastTextError::NotSupervised, fasttext.label_vectors().unwrap_err());
+}
+
+#[test]
+fn test_fasttext_train_with_pretrained_vec() {
+    let input = write_unsupervised_corpus("pretrained_skipgram");
+    let mut embeddings = FastText::new().expect("Failed to create FastText instance");
+    embeddings
+        .train_skipgram(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 12,
+            epoch: 1,
+            min_count: 1,
+            thread: 1,
+            verbose: 0,
+            ..TrainArgs::unsupervised()
+        })
+        .unwrap();
+    let path = std::env::temp_dir().join(format!("fasttext-rs-pretrained-{}.vec", std::process::id()));
+    embeddings.export_vectors(path.to_str().unwrap(), VectorFormat::Text, None).unwrap();
+    let vectors = VectorModel::load_vec(path.to_str().unwrap()).unwrap();
+
+    let input = write_supervised_corpus("pretrained_supervised");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_supervised(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            pretrained_vectors: vectors.path().to_string(),
+            dim: vectors.dim(),
+            epoch: 20,
+            lr: 0.5,
+            thread: 1,
+            verbose: 0,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(12, fasttext.get_dimension().unwrap());
+    let preds = fasttext.predict("oven flour yeast", 1, 0.0).unwrap();
+    assert_eq!("__label__baking", &preds[0].label);
+}
+
+fn progress_args(name: &str) -> Args {
+    let input = write_supervised_corpus(name);
+    TrainArgs {
+        input: input.to_str().unwrap().to_string(),
+        dim: 10,
+        epoch: 50,
+        lr: 0.5,
+        thread: 1,
+        verbose: 0,
+        ..Default::default()
+    }
+    .to_args(ModelName::Supervised)
+    .unwrap()
+}
+
+#[test]
+fn test_fasttext_train_with_progress() {
+    let args = progress_args("train_with_progress");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let mut reported = Vec::new();
+    let status = fasttext
+        .train_with_progress(&args, false, |progress, _loss, lr, _words_per_sec| {
+            reported.push((progress, lr));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+
+    assert_eq!(TrainStatus::Completed, status);
+    assert!(!reported.is_empty());
+    assert!(reported.windows(2).all(|w| w[0].0 <= w[1].0 && w[0].1 >= w[1].1));
+    let preds = fasttext.predict("oven flour yeast", 1, 0.0).unwrap();
+    assert_eq!("__label__baking", &preds[0].label);
+}
+
+#[test]
+fn test_fasttext_train_with_progress_break_keeps_partial_model() {
+    let args = progress_args("train_with_progress_break");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let mut calls = 0;
+    let status = fasttext
+        .train_with_progress(&args, true, |_, _, _, _| {
+            calls += 1;
+            ControlFlow::Break(())
+        })
+        .unwrap();
+
+    assert_eq!(TrainStatus::Aborted, status);
+    assert_eq!(1, calls);
+    assert_eq!(1, fasttext.predict("oven flour yeast", 1, 0.0).unwrap().len());
+}
+
+#[test]
+fn test_fasttext_train_abort_handle_discards_partial_model() {
+    let args = progress_args("train_abort_handle");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let handle = fasttext.abort_handle();
+    handle.clone().abort();
+    let status = fasttext
+        .train_with_progress(&args, false, |_, _, _, _| ControlFlow::Continue(()))
+        .unwrap();
+
+    assert_eq!(TrainStatus::Aborted, status);
+    assert!(!handle.is_aborted());
+    let path = std::env::temp_dir().join(format!("fasttext-rs-discarded-{}.bin", std::process::id()));
+    assert_eq!(FastTextError::ModelNotLoaded, fasttext.save_model(path.to_str().unwrap()).unwrap_err());
+
+    // The abort was consumed, so the next training runs to completion.
+    assert!(fasttext.train(&args).is_ok());
+}
+
+#[test]
+fn test_fasttext_train_with_progress_callback() {
+    let input = write_supervised_corpus("train_with_progress_callback");
+    let args = TrainArgs {
+        input: input.to_str().unwrap().to_string(),
+        dim: 10,
+        epoch: 50,
+        lr: 0.5,
+        verbose: 0,
+        ..Default::default()
+    }
+    .deterministic(true);
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let reported: Arc<Mutex<Vec<TrainProgress>>> = Arc::default();
+    let sink = reported.clone();
+    let status = fasttext
+        .train_with_progress_callback(args.clone(), ModelName::Supervised, false, move |progress| {
+            sink.lock().unwrap().push(progress);
+            Box::pin(async { true })
+        })
+        .unwrap();
+
+    assert_eq!(TrainStatus::Completed, status);
+    let reported = reported.lock().unwrap();
+    assert!(!reported.is_empty());
+    assert!(reported.windows(2).all(|w| w[0].progress <= w[1].progress && w[0].lr >= w[1].lr));
+    let hash = args.training_hash(ModelName::Supervised).unwrap();
+    assert_eq!(Some(hash), fasttext.training_hash().unwrap());
+
+    // Completing with `false` stops the training, which records no hash.
+    let status = fasttext
+        .train_with_progress_callback(args, ModelName::Supervised, true, |_| Box::pin(async { false }))
+        .unwrap();
+    assert_eq!(TrainStatus::Aborted, status);
+    assert_eq!(None, fasttext.training_hash().unwrap());
+    assert_eq!(1, fasttext.predict("oven flour yeast", 1, 0.0).unwrap().len());
+}
+
+#[test]
+fn test_fasttext_load_model_short_trailer() {
+    let (fasttext, _) = train_small_classifier("short_trailer");
+    let bytes = fasttext.save_model_to_vec().unwrap();
+    let path = std::env::temp_dir().join(format!("fasttext-rs-short-trailer-{}.bin", std::process::id()));
+
+    // Bytes too few for the metadata magic are ignored by every loader, like
+    // anything else following the model.
+    for trailer in [&b"\n"[..], b"ab", b"\x00\x00\x00"] {
+        let padded = [bytes.as_slice(), trailer].concat();
+        std::fs::write(&path, &padded).unwrap();
+        let mut loaded = FastText::new().expect("Failed to create FastText instance");
+        loaded.load_model(path.to_str().unwrap()).unwrap();
+        assert_eq!(bytes, loaded.save_model_to_vec().unwrap());
+        loaded.load_model_from_reader(padded.as_slice()).unwrap();
+        assert_eq!(bytes, loaded.save_model_to_vec().unwrap());
+        loaded.load_model_from_buffer(&padded).unwrap();
+        assert_eq!(bytes, loaded.save_model_to_vec().unwrap());
+        loaded.load_model_mmap(path.to_str().unwrap()).unwrap();
+        assert_eq!(bytes, loaded.save_model_to_vec().unwrap());
+    }
+}
+
+#[test]
+fn test_fasttext_train_deterministic() {
+    let input = write_supervised_corpus("train_deterministic");
+    let args = TrainArgs {
+        input: input.to_str().unwrap().to_string(),
+        dim: 10,
+        epoch: 5,
+        lr: 0.5,
+        minn: 2,
+        maxn: 4,
+        thread: 4,
+        seed: 7,
+        verbose: 0,
+        ..Default::default()
+    }
+    .deterministic(true);
+    let hash = args.training_hash(ModelName::Supervised).unwrap();
+    assert_eq!(16, hash.len());
+
+    let train = |args: &TrainArgs| {
+        let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+        fasttext.train_supervised(args.clone()).unwrap();
+        fasttext
+    };
+    assert_eq!(1, args.to_args(ModelName::Supervised).unwrap().thread());
+    let fasttext = train(&args);
+    assert_eq!(Some(hash.clone()), fasttext.training_hash().unwrap());
+    let bytes = fasttext.save_model_to_vec().unwrap();
+    assert_eq!(fasttext.model_size().unwrap(), bytes.len() as u64);
+    assert_eq!(bytes, train(&args).save_model_to_vec().unwrap());
+
+    // The hash survives a round trip through every loader.
+    let path = std::env::temp_dir().join(format!("fasttext-rs-deterministic-{}.bin", std::process::id()));
+    std::fs::write(&path, &bytes).unwrap();
+    let mut loaded = FastText::new().expect("Failed to create FastText instance");
+    loaded.load_model(path.to_str().unwrap()).unwrap();
+    assert_eq!(Some(hash.clone()), loaded.training_hash().unwrap());
+    loaded.load_model_from_buffer(&bytes).unwrap();
+    assert_eq!(Some(hash.clone()), loaded.training_hash().unwrap());
+    loaded.load_model_from_reader(bytes.as_slice()).unwrap();
+    assert_eq!(Some(hash.clone()), loaded.training_hash().unwrap());
+    assert_eq!(bytes, loaded.save_model_to_vec().unwrap());
+    #[cfg(feature = "pure-rust")]
+    {
+        let native = fasttext::api::native::NativeModel::load_model(path.to_str().unwrap()).unwrap();
+        let text = "bake a cake in the oven";
+        let expected = fasttext.predict(text, 1, 0.0).unwrap();
+        let actual = native.predict(text, 1, 0.0).unwrap();
+        assert_eq!(expected[0].label, actual[0].label);
+    }
+
+    // Only the content of the input counts, not where it is.
+    let copy = std::env::temp_dir().join(format!("fasttext-rs-deterministic-{}.txt", std::process::id()));
+    std::fs::copy(&input, &copy).unwrap();
+    let moved = TrainArgs { input: copy.to_str().unwrap().to_string(), verbose: 2, ..args.clone() };
+    assert_eq!(hash, moved.training_hash(ModelName::Supervised).unwrap());
+    assert_ne!(hash, args.training_hash(ModelName::Skipgram).unwrap());
+    assert_ne!(hash, TrainArgs { seed: 8, ..args.clone() }.training_hash(ModelName::Supervised).unwrap());
+
+    // Neither quantizing nor a regular training keep the hash of the trained model.
+    loaded.quantize(QuantizeArgs { cutoff: 500, verbose: 0, ..Default::default() }).unwrap();
+    assert_eq!(None, loaded.training_hash().unwrap());
+    loaded.train_supervised(args.clone().deterministic(false)).unwrap();
+    assert_eq!(None, loaded.training_hash().unwrap());
+
+    let missing = TrainArgs { input: "tests/fixtures/does-not-exist.txt".to_string(), ..args };
+    assert!(matches!(
+        loaded.train_supervised(missing),
+        Err(FastTextError::Io { path: Some(ref path), .. }) if path == "tests/fixtures/does-not-exist.txt"
+    ));
+}
+
+fn train_small_classifier(name: &str) -> (FastText, PathBuf) {
+    let input = write_supervised_corpus(name);
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_supervised(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 16,
+            epoch: 20,
+            lr: 0.5,
+            word_ngrams: 2,
+            bucket: 1000,
+            thread: 1,
+            verbose: 0,
+            ..Default::default()
+        })
+        .unwrap();
+    (fasttext, input)
+}
+
+#[test]
+fn test_fasttext_quantize() {
+    let (mut fasttext, input) = train_small_classifier("quantize");
+    let size = fasttext.model_size().unwrap();
+
+    let quantized_size = fasttext
+        .quantize(QuantizeArgs {
+            input: input.to_str().unwrap().to_string(),
+            cutoff: 500,
+            retrain: true,
+            qnorm: true,
+            thread: 1,
+            verbose: 0,
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(quantized_size < size);
+    assert_eq!(quantized_size, fasttext.model_size().unwrap());
+    let preds = fasttext.predict("oven flour yeast", 1, 0.0).unwrap();
+    assert_eq!("__label__baking", &preds[0].label);
+
+    let path = std::env::temp_dir().join(format!("fasttext-rs-quantize-{}.ftz", std::process::id()));
+    fasttext.save_model(path.to_str().unwrap()).unwrap();
+    assert_eq!(quantized_size, std::fs::metadata(&path).unwrap().len());
+    let mut loaded = FastText::new().expect("Failed to create FastText instance");
+    loaded.load_model(path.to_str().unwrap()).unwrap();
+    let preds = loaded.predict("charcoal steak smoke", 1, 0.0).unwrap();
+    assert_eq!("__label__grilling", &preds[0].label);
+    assert!(loaded.args().unwrap().quantized);
+    assert_eq!(fasttext.args().unwrap(), loaded.args().unwrap());
+
+    // Only the input matrix was quantized, but fastText won't save the output of such a model.
+    let output = std::env::temp_dir().join(format!("fasttext-rs-quantize-{}.vec", std::process::id()));
+    assert_eq!(FastTextError::QuantizedUnsupported, fasttext.save_output(output.to_str().unwrap()).unwrap_err());
+    assert_eq!(FastTextError::QuantizedUnsupported, fasttext.input_matrix().unwrap_err());
+    assert_eq!(2, fasttext.output_matrix().unwrap().rows);
+
+    // There's nothing left to quantize.
+    assert_eq!(FastTextError::QuantizedUnsupported, fasttext.quantize(QuantizeArgs::default()).unwrap_err());
+}
+
+#[test]
+fn test_fasttext_load_model_mmap() {
+    // The input matrix follows the dictionary, so growing one word by a byte
+    // at a time moves it through every alignment of its floats exactly once.
+    let mut unaligned = 0;
+    for padding in 0..4 {
+        let input = write_supervised_corpus(&format!("mmap_{}", padding));
+        let mut corpus = std::fs::read_to_string(&input).unwrap();
+        corpus.push_str(&format!("__label__baking s{}\n", "o".repeat(padding)));
+        std::fs::write(&input, corpus).unwrap();
+
+        let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+        fasttext
+            .train_supervised(TrainArgs {
+                input: input.to_str().unwrap().to_string(),
+                dim: 10,
+                epoch: 5,
+                minn: 2,
+                maxn: 3,
+                bucket: 300,
+                thread: 1,
+                verbose: 0,
+                ..Default::default()
+            })
+            .unwrap();
+        let path = input.with_extension("bin");
+        fasttext.save_model(path.to_str().unwrap()).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        let mut loaded = FastText::new().expect("Failed to create FastText instance");
+        loaded.load_model_mmap(path.to_str().unwrap()).unwrap();
+        let input_matrix = loaded.input_matrix().unwrap();
+        if matches!(input_matrix.data, Cow::Owned(_)) {
+            unaligned += 1;
+        }
+        assert_eq!(fasttext.input_matrix().unwrap().data, input_matrix.data);
+        assert_eq!(fasttext.output_matrix().unwrap().data, loaded.output_matrix().unwrap().data);
+        assert_eq!(
+            fasttext.predict("oven flour yeast", 2, 0.0).unwrap(),
+            loaded.predict("oven flour yeast", 2, 0.0).unwrap()
+        );
+        assert_eq!(fasttext.get_word_vector("ovens").unwrap(), loaded.get_word_vector("ovens").unwrap());
+
+        // The mapping is read-only, so quantizing works on a copy and
+        // leaves the file as it was.
+        loaded.quantize(QuantizeArgs { qnorm: true, verbose: 0, ..Default::default() }).unwrap();
+        assert_eq!(2, loaded.predict("oven flour yeast", 2, 0.0).unwrap().len());
+        assert_eq!(bytes, std::fs::read(&path).unwrap());
+    }
+    // Only the matrix that happens to be aligned for floats is borrowed.
+    assert_eq!(3, unaligned);
+
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let result = fasttext.load_model_mmap("tests/fixtures/does-not-exist.bin");
+    assert!(matches!(result, Err(FastTextError::Io { path: Some(_), .. })));
+    assert_eq!(
+        FastTextError::NotMappable {
+            message: "tests/fixtures/lid.176.ftz: quantized (.ftz) models can't be memory-mapped, use load_model \
+                      instead."
+                .to_string()
+        },
+        fasttext.load_model_mmap("tests/fixtures/lid.176.ftz").unwrap_err()
+    );
+}
+
+#[test]
+fn test_fasttext_load_model_mmap_corrupt_header() {
+    let (mut fasttext, input) = train_small_classifier("mmap_corrupt_header");
+    let path = input.with_extension("bin");
+    fasttext.save_model(path.to_str().unwrap()).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+
+    // The input matrix header holds its rows and columns.
+    let rows = fasttext.nwords().unwrap() as i64 + 1000;
+    let header = [rows.to_le_bytes(), 16i64.to_le_bytes()].concat();
+    let start = bytes.windows(header.len()).position(|window| window == header).unwrap();
+
+    // The second number of rows makes the size of the matrix wrap around to
+    // 64 bytes, which the file has.
+    for (rows, cols) in [(-1i64, 16i64), (16, -1), (i64::MAX, 16), (0x0400_0000_0000_0001, 16)] {
+        let mut corrupt = bytes.clone();
+        corrupt[start..start + 8].copy_from_slice(&rows.to_le_bytes());
+        corrupt[start + 8..start + 16].copy_from_slice(&cols.to_le_bytes());
+        std::fs::write(&path, corrupt).unwrap();
+
+        let mut loaded = FastText::new().expect("Failed to create FastText instance");
+        let result = loaded.load_model_mmap(path.to_str().unwrap());
+        assert!(matches!(result, Err(FastTextError::InvalidModelFormat { .. })), "{:?}", result);
+        assert_eq!(FastTextError::ModelNotLoaded, loaded.get_dimension().unwrap_err());
+    }
+}
+
+#[test]
+fn test_fasttext_quantize_errors() {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, fasttext.quantize(QuantizeArgs::default()).unwrap_err());
+    assert_eq!(FastTextError::ModelNotLoaded, fasttext.model_size().unwrap_err());
+
+    let (mut fasttext, _) = train_small_classifier("quantize_errors");
+    let result = fasttext.quantize(QuantizeArgs {
+        input: "tests/fixtures/does-not-exist.txt".to_string(),
+        cutoff: 10,
+        retrain: true,
+        verbose: 0,
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(FastTextError::Io { .. })));
+    assert!(matches!(
+        fasttext.quantize(QuantizeArgs { dsub: 0, ..Default::default() }),
+        Err(FastTextError::InvalidArgument { .. })
+    ));
+
+    let input = write_unsupervised_corpus("quantize_unsupervised");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_cbow(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 10,
+            epoch: 1,
+            min_count: 1,
+            bucket: 1000,
+            thread: 1,
+            verbose: 0,
+            ..TrainArgs::unsupervised()
+        })
+        .unwrap();
+    assert_eq!(FastTextError::NotSupervised, fasttext.quantize(QuantizeArgs::default()).unwrap_err());
+}
+
+#[test]
+fn test_fasttext_args() {
+    let (mut fasttext, _) = train_small_classifier("args");
+    let expected = ModelArgs {
+        model: ModelName::Supervised,
+        loss: Loss::Softmax,
+        dim: 16,
+        ws: 5,
+        epoch: 20,
+        min_count: 1,
+        neg: 5,
+        word_ngrams: 2,
+        bucket: 1000,
+        minn: 0,
+        maxn: 0,
+        lr_update_rate: 100,
+        t: 1e-4,
+        quantized: false,
+        qout: false,
+    };
+    assert_eq!(expected, fasttext.args().unwrap());
+
+    let path = std::env::temp_dir().join(format!("fasttext-rs-args-{}.bin", std::process::id()));
+    fasttext.save_model(path.to_str().unwrap()).unwrap();
+    let mut loaded = FastText::new().expect("Failed to create FastText instance");
+    loaded.load_model(path.to_str().unwrap()).unwrap();
+    assert_eq!(expected, loaded.args().unwrap());
+
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.args().unwrap_err());
+}
+
+#[test]
+fn test_fasttext_dictionary() {
+    let (fasttext, _) = train_small_classifier("dictionary");
+
+    let mut labels = fasttext.labels().unwrap();
+    labels.sort();
+    assert_eq!(
+        vec![("__label__baking".to_string(), 100), ("__label__grilling".to_string(), 100)],
+        labels
+    );
+    assert_eq!(2, fasttext.nlabels().unwrap());
+
+    // Every line ends with an end of sentence token, the most frequent word.
+    let words = fasttext.words().unwrap();
+    assert_eq!(17, fasttext.nwords().unwrap());
+    assert_eq!(17, words.len());
+    assert_eq!(("</s>".to_string(), 200), words[0]);
+    assert_eq!(1400, words.iter().map(|(_, count)| count).sum::<i64>());
+    for (id, (word, _)) in words.iter().enumerate() {
+        assert_eq!(id as i32, fasttext.get_word_id(word).unwrap());
+    }
+    assert_eq!(1600, fasttext.ntokens().unwrap());
+
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.words().unwrap_err());
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.nlabels().unwrap_err());
+}
+
+#[test]
+fn test_fasttext_explain() {
+    let input = write_supervised_corpus("explain");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext
+        .train_supervised(TrainArgs {
+            input: input.to_str().unwrap().to_string(),
+            dim: 10,
+            epoch: 20,
+            lr: 0.5,
+            word_ngrams: 2,
+            bucket: 1000,
+            loss: Loss::Ova,
+            thread: 1,
+            verbose: 0,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let contributions = fasttext.explain("oven flour charcoal", "__label__baking").unwrap();
+    let tokens: Vec<(&str, NgramKind)> =
+        contributions.iter().map(|c| (c.token.as_str(), c.ngram_kind)).collect();
+    assert_eq!(
+        vec![
+            ("oven", NgramKind::Word),
+            ("flour", NgramKind::Word),
+            ("charcoal", NgramKind::Word),
+            ("oven flour", NgramKind::WordNgram),
+            ("flour charcoal", NgramKind::WordNgram),
+        ],
+        tokens
+    );
+    assert!(contributions[0].score > 0.0);
+    assert!(contributions[2].score < 0.0);
+
+    // With one-vs-all, the probability of a label is the sigmoid of its logit,
+    // which fastText looks up in a coarse table.
+    let logit: f32 = contributions.iter().map(|c| c.score).sum();
+    let probability = fasttext
+        .predict("oven flour charcoal", -1, 0.0)
+        .unwrap()
+        .into_iter()
+        .find(|p| p.label == "__label__baking")
+        .unwrap()
+        .probability;
+    assert!((probability - 1.0 / (1.0 + (-logit).exp())).abs() < 1e-2);
+
+    // A newline ends the text as the `</s>` word, which counts towards the
+    // logit and pairs with the last word.
+    let contributions = fasttext.explain("oven flour\ncharcoal", "__label__baking").unwrap();
+    let tokens: Vec<(&str, NgramKind)> =
+        contributions.iter().map(|c| (c.token.as_str(), c.ngram_kind)).collect();
+    assert_eq!(("</s>", NgramKind::EndOfLine), tokens[2]);
+    assert_eq!(vec![("oven", NgramKind::Word), ("flour", NgramKind::Word)], tokens[..2]);
+    assert!(tokens[3..].iter().all(|(_, kind)| *kind == NgramKind::WordNgram));
+    let logit: f32 = contributions.iter().map(|c| c.score).sum();
+    let probability = fasttext
+        .predict("oven flour\n", -1, 0.0)
+        .unwrap()
+        .into_iter()
+        .find(|p| p.label == "__label__baking")
+        .unwrap()
+        .probability;
+    assert!((probability - 1.0 / (1.0 + (-logit).exp())).abs() < 1e-2);
+
+    assert!(matches!(
+        fasttext.explain("oven", "__label__frying"),
+        Err(FastTextError::InvalidArgument { .. })
+    ));
+    assert!(matches!(fasttext.explain("oven", "oven"), Err(FastTextError::InvalidArgument { .. })));
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    assert_eq!(FastTextError::ModelNotLoaded, untrained.explain("oven", "__label__baking").unwrap_err());
+}
+
+fn autotune_config(name: &str) -> AutotuneConfig {
+    let validation = write_supervised_corpus(&format!("{}_validation", name));
+    AutotuneConfig {
+        validation_file: validation.to_str().unwrap().to_string(),
+        duration: 2,
+        args: TrainArgs { dim: 10, thread: 1, verbose: 0, ..Default::default() },
+        fixed: vec![Hyperparameter::Dim],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_fasttext_autotune() {
+    let input = write_supervised_corpus("autotune");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let mut trials = Vec::new();
+    let result = fasttext
+        .autotune_with_progress(input.to_str().unwrap(), autotune_config("autotune"), |trial| {
+            trials.push(trial.clone());
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+
+    assert!(!trials.is_empty());
+    assert!(result.trials as usize >= trials.len());
+    assert!(trials.iter().enumerate().all(|(i, trial)| trial.trial == i as i32 + 1));
+    assert!(trials.iter().all(|trial| trial.args.dim == 10 && trial.quantize.is_none()));
+    let best = trials.iter().map(|trial| trial.score).fold(f64::NAN, f64::max);
+    assert_eq!(best, result.score);
+    assert_eq!(10, result.args.dim);
+    assert_eq!(0, result.args.verbose);
+    assert_eq!(10, fasttext.get_dimension().unwrap());
+    let preds = fasttext.predict("oven flour yeast", 1, 0.0).unwrap();
+    assert_eq!("__label__baking", &preds[0].label);
+}
+
+#[test]
+fn test_fasttext_autotune_break_keeps_best_trial() {
+    let input = write_supervised_corpus("autotune_break");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let mut scores = Vec::new();
+    let config = AutotuneConfig {
+        metric: AutotuneMetric::F1Label("__label__grilling".to_string()),
+        duration: 60,
+        ..autotune_config("autotune_break")
+    };
+    let result = fasttext
+        .autotune_with_progress(input.to_str().unwrap(), config, |trial| {
+            scores.push(trial.score);
+            ControlFlow::Break(())
+        })
+        .unwrap();
+
+    assert_eq!(1, result.trials);
+    assert_eq!(vec![result.score], scores);
+    assert!(fasttext.predict("charcoal steak smoke", 1, 0.0).is_ok());
+}
+
+#[test]
+fn test_fasttext_autotune_with_progress_callback() {
+    let input = write_supervised_corpus("autotune_callback");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    let scores: Arc<Mutex<Vec<f64>>> = Arc::default();
+    let sink = scores.clone();
+    let config = AutotuneConfig { duration: 60, ..autotune_config("autotune_callback") };
+    let result = fasttext
+        .autotune_with_progress_callback(input.to_str().unwrap(), config, move |trial| {
+            sink.lock().unwrap().push(trial.score);
+            Box::pin(async { false })
+        })
+        .unwrap();
+
+    assert_eq!(1, result.trials);
+    assert_eq!(vec![result.score], *scores.lock().unwrap());
+    assert!(fasttext.predict("oven flour yeast", 1, 0.0).is_ok());
+}
+
+#[test]
+fn test_fasttext_autotune_errors() {
+    let input = write_supervised_corpus("autotune_errors");
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+
+    let config = AutotuneConfig {
+        validation_file: "tests/fixtures/does-not-exist.txt".to_string(),
+        ..autotune_config("autotune_errors")
+    };
+    assert!(matches!(
+        fasttext.autotune(input.to_str().unwrap(), config),
+        Err(FastTextError::Io { .. })
+    ));
+
+    // The label is only looked up once the first model is trained.
+    let config = AutotuneConfig {
+        metric: AutotuneMetric::PrecisionAtRecall { recall: 0.5, label: Some("__label__nope".to_string()) },
+        ..autotune_config("autotune_errors")
+    };
+    let err = fasttext.autotune(input.to_str().unwrap(), config).unwrap_err();
+    assert_eq!(FastTextError::Cpp { message: "Unknown autotune metric label".to_string() }, err);
+    // The model of the failed search isn't kept.
+    assert!(fasttext.model_size().is_err());
+}
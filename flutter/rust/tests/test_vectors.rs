@@ -0,0 +1,180 @@
+#![cfg(feature = "cpp")]
+
+use fasttext::api::error::FastTextError;
+use fasttext::api::fasttext::FastText;
+use fasttext::api::vectors::{VectorFormat, VectorModel};
+use std::path::PathBuf;
+
+fn load_lid_model() -> FastText {
+    let mut fasttext = FastText::new().expect("Failed to create FastText instance");
+    fasttext.load_model("tests/fixtures/lid.176.ftz").unwrap();
+    fasttext
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fasttext-rs-{}-{}", name, std::process::id()))
+}
+
+fn read_f32s(bytes: &[u8]) -> Vec<f32> {
+    bytes.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect()
+}
+
+#[test]
+fn test_fasttext_export_vectors_text() {
+    let fasttext = load_lid_model();
+    let path = temp_path("export.vec");
+    fasttext.export_vectors(path.to_str().unwrap(), VectorFormat::Text, Some(50)).unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(Some("50 16"), lines.next());
+    let words = fasttext.words().unwrap();
+    for (line, (word, _)) in lines.zip(&words) {
+        let mut fields = line.split(' ');
+        assert_eq!(Some(word.as_str()), fields.next());
+        let values: Vec<f32> = fields.map(|v| v.parse().unwrap()).collect();
+        assert_eq!(fasttext.get_word_vector(word).unwrap(), values);
+    }
+    assert_eq!(51, text.lines().count());
+
+    // Without a limit, every word is exported.
+    fasttext.export_vectors(path.to_str().unwrap(), VectorFormat::Text, None).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(format!("{} 16", words.len()), text.lines().next().unwrap());
+    assert_eq!(words.len() + 1, text.lines().count());
+}
+
+#[test]
+fn test_fasttext_export_vectors_word2vec_binary() {
+    let fasttext = load_lid_model();
+    let path = temp_path("export.bin");
+    fasttext.export_vectors(path.to_str().unwrap(), VectorFormat::Word2VecBinary, Some(20)).unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    let mut rest = bytes.strip_prefix(b"20 16\n".as_slice()).unwrap();
+    for (word, _) in fasttext.words().unwrap().iter().take(20) {
+        let space = rest.iter().position(|b| *b == b' ').unwrap();
+        assert_eq!(word.as_bytes(), &rest[..space]);
+        assert_eq!(fasttext.get_word_vector(word).unwrap(), read_f32s(&rest[space + 1..space + 1 + 64]));
+        assert_eq!(b'\n', rest[space + 1 + 64]);
+        rest = &rest[space + 2 + 64..];
+    }
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn test_fasttext_export_vectors_npy() {
+    let fasttext = load_lid_model();
+    let path = temp_path("export.npy");
+    fasttext.export_vectors(path.to_str().unwrap(), VectorFormat::Npy, Some(10)).unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(b"\x93NUMPY\x01\x00", &bytes[..8]);
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    assert_eq!(0, (10 + header_len) % 64);
+    let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+    assert!(header.starts_with("{'descr': '<f4', 'fortran_order': False, 'shape': (10, 16), }"));
+    assert!(header.ends_with('\n'));
+
+    let data = read_f32s(&bytes[10 + header_len..]);
+    assert_eq!(10 * 16, data.len());
+    for (row, (word, _)) in data.chunks(16).zip(fasttext.words().unwrap()) {
+        assert_eq!(fasttext.get_word_vector(&word).unwrap(), row);
+    }
+}
+
+#[test]
+fn test_fasttext_export_vectors_errors() {
+    let fasttext = load_lid_model();
+    let path = "tests/fixtures/missing-dir/export.vec";
+    assert!(matches!(
+        fasttext.export_vectors(path, VectorFormat::Text, None),
+        Err(FastTextError::Io { path: Some(p), .. }) if p == path
+    ));
+
+    let untrained = FastText::new().expect("Failed to create FastText instance");
+    let path = temp_path("export-untrained.vec");
+    assert_eq!(
+        FastTextError::ModelNotLoaded,
+        untrained.export_vectors(path.to_str().unwrap(), VectorFormat::Text, None).unwrap_err()
+    );
+}
+
+#[test]
+fn test_vector_model_load_vec() {
+    let fasttext = load_lid_model();
+    let path = temp_path("load.vec");
+    fasttext.export_vectors(path.to_str().unwrap(), VectorFormat::Text, None).unwrap();
+
+    let vectors = VectorModel::load_vec(path.to_str().unwrap()).unwrap();
+    assert_eq!(path.to_str().unwrap(), vectors.path());
+    assert_eq!(16, vectors.dim());
+    let words = fasttext.words().unwrap();
+    assert_eq!(words.len(), vectors.words().len());
+    for (word, _) in words.iter().take(100) {
+        assert_eq!(fasttext.get_word_vector(word).unwrap(), vectors.get_word_vector(word).unwrap());
+    }
+    // Without subwords, an unknown word has no vector to speak of.
+    assert_eq!(vec![0.0; 16], vectors.get_word_vector("notaword").unwrap());
+
+    // Both see the same vectors for the words of the dictionary.
+    let word = &words[10].0;
+    let expected = fasttext.get_nn(word, 5).unwrap();
+    let neighbors = vectors.get_nn(word, 5).unwrap();
+    assert_eq!(5, neighbors.len());
+    for ((expected_similarity, expected_word), (similarity, word)) in expected.iter().zip(&neighbors) {
+        assert_eq!(expected_word, word);
+        assert!((expected_similarity - similarity).abs() < 1e-5);
+    }
+    let (a, b, c) = (&words[1].0, &words[2].0, &words[3].0);
+    let expected = fasttext.get_analogies(3, a, b, c).unwrap();
+    let analogies = vectors.get_analogies(3, a, b, c).unwrap();
+    assert_eq!(
+        expected.iter().map(|(_, w)| w).collect::<Vec<_>>(),
+        analogies.iter().map(|(_, w)| w).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_vector_model_load_vec_duplicates() {
+    let path = temp_path("duplicates.vec");
+    std::fs::write(&path, "4 2\nfoo 1 0\nbar 0 1\nfoo -1 0\nbaz 1 1\n").unwrap();
+
+    // The last vector of a word replaces the earlier one in place.
+    let vectors = VectorModel::load_vec(path.to_str().unwrap()).unwrap();
+    assert_eq!(vec!["foo", "bar", "baz"], vectors.words());
+    assert_eq!(vec![-1.0, 0.0], vectors.get_word_vector("foo").unwrap());
+    let neighbors = vectors.get_nn("baz", 5).unwrap();
+    assert_eq!(vec!["bar", "foo"], neighbors.iter().map(|(_, w)| w).collect::<Vec<_>>());
+    assert!(neighbors[1].0 < 0.0);
+}
+
+#[test]
+fn test_vector_model_load_vec_errors() {
+    assert!(matches!(
+        VectorModel::load_vec("tests/fixtures/missing.vec"),
+        Err(FastTextError::Io { path: Some(_), .. })
+    ));
+
+    let invalid = [
+        ("", "1: the header is missing"),
+        ("2 3\nfoo 1 2 3\n", "3: expected 2 vectors, found 1"),
+        ("1 3\nfoo 1 2\n", "2: expected 3 values, found 2"),
+        ("1 2\nfoo 1 bar\n", "2: bar isn't a number"),
+        ("two 2\n", "1: the header isn't `count dim`"),
+        ("18446744073709551615 2\n", "1: the header is too large"),
+        // Only as much memory as the file needs is allocated.
+        ("99999999999 9999999\nfoo 1 2\n", "2: expected 9999999 values, found 2"),
+        ("99999999999 2\nfoo 1 2\n", "3: expected 99999999999 vectors, found 1"),
+    ];
+    let path = temp_path("invalid.vec");
+    for (content, reason) in invalid {
+        std::fs::write(&path, content).unwrap();
+        match VectorModel::load_vec(path.to_str().unwrap()) {
+            Err(FastTextError::InvalidModelFormat { message }) => {
+                assert_eq!(format!("{}:{}", path.to_str().unwrap(), reason), message)
+            }
+            _ => panic!("{:?} should be invalid", content),
+        }
+    }
+}
@@ -46,7 +46,7 @@ template <class T> class AlignedVector {
       std::copy(first, last, begin());
     }
 
-    AlignedVector(AlignedVector &&from) noexcept : mem_(from.mem_), size_(from.size_) {
+    AlignedVector(AlignedVector &&from) noexcept : mem_(from.mem_), size_(from.size_), owned_(from.owned_) {
       from.mem_ = nullptr;
       from.size_ = 0;
     }
@@ -56,11 +56,21 @@ template <class T> class AlignedVector {
       release();
       mem_ = from.mem_;
       size_ = from.size_;
+      owned_ = from.owned_;
       from.mem_ = nullptr;
       from.size_ = 0;
       return *this;
     }
 
+    // Wraps memory owned by someone else, which must outlive the vector.
+    static AlignedVector borrow(T *mem, std::size_t size) {
+      AlignedVector vector;
+      vector.mem_ = mem;
+      vector.size_ = size;
+      vector.owned_ = false;
+      return vector;
+    }
+
     AlignedVector(const AlignedVector&) = delete;
     AlignedVector& operator=(const AlignedVector&) = delete;
 
@@ -85,8 +95,10 @@ template <class T> class AlignedVector {
   private:
     T *mem_;
     std::size_t size_;
+    bool owned_ = true;
 
     void release() {
+      if (!owned_) return;
 #ifdef _MSC_VER
       _aligned_free(mem_);
 #else
@@ -213,7 +213,8 @@ Autotune::Autotune(const std::shared_ptr<FastText>& fastText)
       sizeConstraintFailed_(0),
       continueTraining_(false),
       strategy_(),
-      timer_() {}
+      timer_(),
+      previousSignalHandler_(SIG_DFL) {}
 
 void Autotune::printInfo(double maxDuration) {
   double progress = elapsed_ * 100 / maxDuration;
@@ -261,19 +262,30 @@ void Autotune::abort() {
 void Autotune::startTimer(const Args& args) {
   std::chrono::steady_clock::time_point start =
       std::chrono::steady_clock::now();
-  timer_ = std::thread([=]() { timer(start, args.autotuneDuration); });
   bestScore_ = kUnknownBestScore;
   trials_ = 0;
+  // The timer stops as soon as it sees `continueTraining_` unset, so it must
+  // be set before the thread starts.
   continueTraining_ = true;
+  timer_ = std::thread([=]() { timer(start, args.autotuneDuration); });
 
-  auto previousSignalHandler = std::signal(SIGINT, signalHandler);
-  interruptSignalHandler = [&]() {
-    std::signal(SIGINT, previousSignalHandler);
+  previousSignalHandler_ = std::signal(SIGINT, signalHandler);
+  interruptSignalHandler = [this]() {
+    std::signal(SIGINT, previousSignalHandler_);
     std::cerr << std::endl << "Aborting autotune..." << std::endl;
     abort();
   };
 }
 
+void Autotune::stopTimer() {
+  // Destroying the thread while it is joinable would terminate the program.
+  if (timer_.joinable()) {
+    timer_.join();
+  }
+  // The handler refers to this instance, don't leave it installed.
+  std::signal(SIGINT, previousSignalHandler_);
+}
+
 double Autotune::getMetricScore(
     Meter& meter,
     const metric_name& metricName,
@@ -380,7 +392,15 @@ void Autotune::printSkippedArgs(const Args& autotuneArgs) {
   }
 }
 
-void Autotune::train(const Args& autotuneArgs) {
+double Autotune::getBestScore() const {
+  return bestScore_;
+}
+
+int32_t Autotune::getTrials() const {
+  return trials_;
+}
+
+void Autotune::train(const Args& autotuneArgs, const TrialCallback& callback) {
   std::ifstream validationFileStream(autotuneArgs.autotuneValidationFile);
   if (!validationFileStream.is_open()) {
     throw std::invalid_argument("Validation file cannot be opened!");
@@ -443,13 +463,19 @@ void Autotune::train(const Args& autotuneArgs) {
       break;
     } catch (FastText::AbortError&) {
       break;
+    } catch (...) {
+      abort();
+      stopTimer();
+      throw;
     }
     LOG_VAL_NAN(currentScore, currentScore)
     LOG_VAL(train took, elapsedTimeMarker.getElapsed())
+    if (callback && !callback(trials_, trainArgs, currentScore)) {
+      abort();
+      break;
+    }
   }
-  if (timer_.joinable()) {
-    timer_.join();
-  }
+  stopTimer();
 
   if (bestScore_ == kUnknownBestScore) {
     std::string errorMessage;
@@ -8,6 +8,7 @@
 
 #pragma once
 
+#include <functional>
 #include <istream>
 #include <memory>
 #include <random>
@@ -50,6 +51,7 @@ class Autotune {
   std::atomic<bool> continueTraining_;
   std::unique_ptr<AutotuneStrategy> strategy_;
   std::thread timer_;
+  void (*previousSignalHandler_)(int);
 
   bool keepTraining(double maxDuration) const;
   void printInfo(double maxDuration);
@@ -58,6 +60,7 @@ class Autotune {
       double maxDuration);
   void abort();
   void startTimer(const Args& args);
+  void stopTimer();
   double getMetricScore(
       Meter& meter,
       const metric_name& metricName,
@@ -83,7 +86,17 @@ class Autotune {
   Autotune& operator=(Autotune&&) = delete;
   ~Autotune() noexcept = default;
 
-  void train(const Args& args);
+  /**
+   * Called after every trial with its number, arguments and score, which is
+   * NaN if the trial failed. Returning false stops the search, the model is
+   * then trained again with the best arguments found so far.
+   */
+  using TrialCallback =
+      std::function<bool(int32_t trial, const Args& args, double score)>;
+
+  void train(const Args& args, const TrialCallback& callback = nullptr);
+  double getBestScore() const;
+  int32_t getTrials() const;
 };
 
 } // namespace fasttext
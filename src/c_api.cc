@@ -1,13 +1,68 @@
 #include "c_api.h"
 
+#include <algorithm>
+#include <atomic>
 #include <cstring>
+#include <exception>
+#include <fstream>
+#include <memory>
+#include <stdexcept>
 #include <sstream>
 #include <string>
+#include <thread>
 #include <vector>
 
+#include "autotune.h"
 #include "fasttext.h"
+#include "meter.h"
+
+namespace {
+    /// An exception carrying one of the `FASTTEXT_ERROR_*` codes.
+    class CApiError : public std::runtime_error {
+    public:
+        CApiError(const uint32_t code, const std::string& message) : std::runtime_error(message), code(code) {}
+
+        const uint32_t code;
+    };
+
+    char* _copyString(const std::string& str) {
+        auto* copy = new char[str.length() + 1];
+        strcpy(copy, str.c_str());
+        return copy;
+    }
+
+    /// Fills the error of a result from the exception being handled.
+    ///
+    /// The message is copied since `what()` doesn't outlive the exception.
+    template <typename Result>
+    void _setError(Result& result) {
+        try {
+            throw;
+        } catch (const CApiError& e) {
+            result.error_code = e.code;
+            result.error = _copyString(e.what());
+        } catch (const std::exception& e) {
+            result.error_code = FASTTEXT_ERROR_CPP;
+            result.error = _copyString(e.what());
+        } catch (...) {
+            result.error_code = FASTTEXT_ERROR_CPP;
+            result.error = _copyString("An unknown exception occurred!");
+        }
+    }
+
+    void _checkReadable(const std::string& path, const std::string& purpose) {
+        std::ifstream ifs(path);
+        if (!ifs.is_open()) {
+            throw CApiError(FASTTEXT_ERROR_IO, path + " cannot be opened for " + purpose + "!");
+        }
+    }
+}
 
 extern "C" {
+    void fasttext_free_error(const char* error) {
+        delete[] error;
+    }
+
     /* --- Args --- */
 
     struct fasttext_args_t {
@@ -25,9 +80,15 @@ extern "C" {
         delete args;
     }
 
-    void fasttext_args_parse(const fasttext_args_t* args, const int argc, char** argv) {
-        const std::vector<std::string> args_vec(argv, argv + argc);
-        args->args->parseArgs(args_vec);
+    VoidResult fasttext_args_parse(const fasttext_args_t* args, const int argc, char** argv) {
+        VoidResult result = {};
+        try {
+            const std::vector<std::string> args_vec(argv, argv + argc);
+            args->args->parseArgs(args_vec);
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
     }
 
     const char* fasttext_args_get_input(const fasttext_args_t* args) { return args->args->input.c_str(); }
@@ -96,6 +157,9 @@ extern "C" {
     int fasttext_args_get_save_output(const fasttext_args_t* args) { return args->args->saveOutput; }
     void fasttext_args_set_save_output(const fasttext_args_t* args, const int save_output) { args->args->saveOutput = save_output; }
 
+    int fasttext_args_get_seed(const fasttext_args_t* args) { return args->args->seed; }
+    void fasttext_args_set_seed(const fasttext_args_t* args, const int seed) { args->args->seed = seed; }
+
     int fasttext_args_get_qout(const fasttext_args_t* args) { return args->args->qout; }
     void fasttext_args_set_qout(const fasttext_args_t* args, const int qout) { args->args->qout = qout; }
 
@@ -111,24 +175,60 @@ extern "C" {
     size_t fasttext_args_get_dsub(const fasttext_args_t* args) { return args->args->dsub; }
     void fasttext_args_set_dsub(const fasttext_args_t* args, const size_t dsub) { args->args->dsub = dsub; }
 
+    const char* fasttext_args_get_autotune_validation_file(const fasttext_args_t* args) { return args->args->autotuneValidationFile.c_str(); }
+    void fasttext_args_set_autotune_validation_file(const fasttext_args_t* args, const char* validation_file) { args->args->autotuneValidationFile = validation_file; }
+
+    const char* fasttext_args_get_autotune_metric(const fasttext_args_t* args) { return args->args->autotuneMetric.c_str(); }
+    void fasttext_args_set_autotune_metric(const fasttext_args_t* args, const char* metric) { args->args->autotuneMetric = metric; }
+
+    int fasttext_args_get_autotune_predictions(const fasttext_args_t* args) { return args->args->autotunePredictions; }
+    void fasttext_args_set_autotune_predictions(const fasttext_args_t* args, const int predictions) { args->args->autotunePredictions = predictions; }
+
+    int fasttext_args_get_autotune_duration(const fasttext_args_t* args) { return args->args->autotuneDuration; }
+    void fasttext_args_set_autotune_duration(const fasttext_args_t* args, const int duration) { args->args->autotuneDuration = duration; }
+
+    const char* fasttext_args_get_autotune_model_size(const fasttext_args_t* args) { return args->args->autotuneModelSize.c_str(); }
+    void fasttext_args_set_autotune_model_size(const fasttext_args_t* args, const char* model_size) { args->args->autotuneModelSize = model_size; }
+
+    bool fasttext_args_is_manual(const fasttext_args_t* args, const char* name) { return args->args->isManual(name); }
+    void fasttext_args_set_manual(const fasttext_args_t* args, const char* name) { args->args->setManual(name); }
+
     /* --- FastText --- */
 
     struct fasttext_t {
         fasttext::FastText* ft;
     };
 
+    void _checkLoaded(const fasttext_t* ft) {
+        // Most of `fasttext::FastText` dereferences the model without checking.
+        if (!ft->ft->getDictionary()) {
+            throw CApiError(FASTTEXT_ERROR_MODEL_NOT_LOADED, "No model was loaded or trained!");
+        }
+    }
+
+    void _checkSupervised(const fasttext_t* ft) {
+        _checkLoaded(ft);
+        if (ft->ft->getArgs().model != fasttext::model_name::sup) {
+            throw CApiError(FASTTEXT_ERROR_NOT_SUPERVISED, "Model needs to be supervised!");
+        }
+    }
+
+    void _checkNotQuantized(const fasttext_t* ft) {
+        if (ft->ft->isQuant()) {
+            throw CApiError(FASTTEXT_ERROR_QUANTIZED_UNSUPPORTED, "Not supported by quantized models!");
+        }
+    }
+
     FastTextResult fasttext_new() {
-        const auto result = new FastTextResult();
+        FastTextResult result = {};
         try {
             auto* c_ft = new fasttext_t();
             c_ft->ft = new fasttext::FastText();
-            result->result = c_ft;
-        } catch (const std::exception& e) {
-            result->error = e.what();
+            result.result = c_ft;
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
     }
 
     void fasttext_delete(const fasttext_t* ft) {
@@ -137,27 +237,118 @@ extern "C" {
     }
 
     VoidResult fasttext_load_model(const fasttext_t* ft, const char* path) {
-        const auto result = new VoidResult();
+        VoidResult result = {};
         try {
-            ft->ft->loadModel(std::string(path));
-        } catch (const std::exception& e) {
-            result->error = e.what();
+            _checkReadable(path, "loading");
+            try {
+                ft->ft->loadModel(std::string(path));
+            } catch (const std::invalid_argument& e) {
+                throw CApiError(FASTTEXT_ERROR_INVALID_MODEL_FORMAT, e.what());
+            }
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
     }
 
     VoidResult fasttext_load_model_from_buffer(const fasttext_t* ft, const void* data, const size_t size) {
-        const auto result = new VoidResult();
+        VoidResult result = {};
         try {
-            ft->ft->loadModelFromBuffer(data, size);
-        } catch (const std::exception& e) {
-            result->error = e.what();
+            try {
+                ft->ft->loadModelFromBuffer(data, size);
+            } catch (const std::invalid_argument& e) {
+                throw CApiError(FASTTEXT_ERROR_INVALID_MODEL_FORMAT, e.what());
+            }
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    /// Pulls the bytes of an input stream from a `fasttext_read_callback_t`.
+    class CallbackReadBuffer : public std::streambuf {
+    public:
+        CallbackReadBuffer(const fasttext_read_callback_t read, void* user_data)
+            : read_(read), user_data_(user_data), buffer_(1 << 16) {}
+
+        bool failed() const {
+            return failed_;
+        }
+
+    protected:
+        int_type underflow() override {
+            if (gptr() < egptr()) {
+                return traits_type::to_int_type(*gptr());
+            }
+            if (failed_) {
+                return traits_type::eof();
+            }
+            const int64_t n = read_(buffer_.data(), buffer_.size(), user_data_);
+            if (n <= 0) {
+                failed_ = n < 0;
+                return traits_type::eof();
+            }
+            consumed_ += egptr() - eback();
+            setg(buffer_.data(), buffer_.data(), buffer_.data() + n);
+            return traits_type::to_int_type(*gptr());
+        }
+
+        // The input can't be rewound, but callers like `FastText::test` seek
+        // to the start before reading, which is fine as long as nothing was read.
+        pos_type seekoff(const off_type off, const std::ios_base::seekdir dir, std::ios_base::openmode) override {
+            const off_type position = consumed_ + (gptr() - eback());
+            if (off == 0 && (dir == std::ios_base::cur || (dir == std::ios_base::beg && position == 0))) {
+                return position;
+            }
+            return pos_type(off_type(-1));
+        }
+
+        pos_type seekpos(const pos_type pos, const std::ios_base::openmode which) override {
+            return seekoff(off_type(pos), std::ios_base::beg, which);
+        }
+
+    private:
+        fasttext_read_callback_t read_;
+        void* user_data_;
+        std::vector<char> buffer_;
+        off_type consumed_ = 0;
+        bool failed_ = false;
+    };
+
+    VoidResult fasttext_load_model_from_reader(const fasttext_t* ft, const fasttext_read_callback_t read, void* user_data) {
+        VoidResult result = {};
+        try {
+            CallbackReadBuffer buffer(read, user_data);
+            std::istream in(&buffer);
+            try {
+                ft->ft->loadModelFromStream(in);
+            } catch (const std::invalid_argument& e) {
+                if (buffer.failed()) {
+                    throw CApiError(FASTTEXT_ERROR_IO, "Failed to read the model!");
+                }
+                throw CApiError(FASTTEXT_ERROR_INVALID_MODEL_FORMAT, e.what());
+            }
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
+    }
+
+    VoidResult fasttext_load_model_mmap(const fasttext_t* ft, const char* path) {
+        VoidResult result = {};
+        try {
+            _checkReadable(path, "loading");
+            try {
+                ft->ft->loadModelMapped(std::string(path));
+            } catch (const fasttext::DenseMatrix::NotMappableError& e) {
+                throw CApiError(FASTTEXT_ERROR_NOT_MAPPABLE, e.what());
+            } catch (const std::invalid_argument& e) {
+                throw CApiError(FASTTEXT_ERROR_INVALID_MODEL_FORMAT, e.what());
+            }
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
     }
 
     void fasttext_free_float_char_pair(const fasttext_float_char_pair_t* nns, const size_t n_nn) {
@@ -168,13 +359,14 @@ extern "C" {
     }
 
     FloatCharPairResult fasttext_get_nn(const fasttext_t* ft, const char* word, const int32_t k, size_t* n_neighbors) {
-        const auto result = new FloatCharPairResult();
+        FloatCharPairResult result = {};
         try {
+            _checkLoaded(ft);
             const std::vector<std::pair<fasttext::real, std::string>> neighbors = ft->ft->getNN(std::string(word), k);
 
             *n_neighbors = neighbors.size();
             if (*n_neighbors == 0) {
-                result->result = nullptr;
+                result.result = nullptr;
             } else {
                 auto* c_neighbors = new fasttext_float_char_pair_t[*n_neighbors];
                 for (size_t i = 0; i < *n_neighbors; ++i) {
@@ -182,14 +374,12 @@ extern "C" {
                     c_neighbors[i].second = new char[neighbors[i].second.length() + 1];
                     strcpy(c_neighbors[i].second, neighbors[i].second.c_str());
                 }
-                result->result = c_neighbors;
+                result.result = c_neighbors;
             }
-        } catch (const std::exception& e) {
-            result->error = e.what();
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
     }
 
     FloatCharPairResult fasttext_get_analogies(
@@ -200,8 +390,9 @@ extern "C" {
         const char* wordC,
         size_t* n_analogies
     ) {
-        const auto result = new FloatCharPairResult();
+        FloatCharPairResult result = {};
         try {
+            _checkLoaded(ft);
             const std::vector<std::pair<fasttext::real, std::string>> analogies = ft->ft->getAnalogies(
                 k,
                 std::string(wordA),
@@ -211,7 +402,7 @@ extern "C" {
 
             *n_analogies = analogies.size();
             if (*n_analogies == 0) {
-                result->result = nullptr;
+                result.result = nullptr;
             } else {
                 auto* c_analogies = new fasttext_float_char_pair_t[*n_analogies];
                 for (size_t i = 0; i < *n_analogies; ++i) {
@@ -219,100 +410,404 @@ extern "C" {
                     c_analogies[i].second = new char[analogies[i].second.length() + 1];
                     strcpy(c_analogies[i].second, analogies[i].second.c_str());
                 }
-                result->result = c_analogies;
+                result.result = c_analogies;
             }
-        } catch (const std::exception& e) {
-            result->error = e.what();
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
     }
 
     Int32Result fasttext_get_word_id(const fasttext_t *ft, const char *word) {
-        const auto result = new Int32Result();
+        Int32Result result = {};
         try {
-            result->result = ft->ft->getWordId(std::string(word));
-        } catch (const std::exception& e) {
-            result->error = e.what();
+            _checkLoaded(ft);
+            result.result = ft->ft->getWordId(std::string(word));
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
     }
 
     Int32Result fasttext_get_subword_id(const fasttext_t *ft, const char *word) {
-        const auto result = new Int32Result();
+        Int32Result result = {};
         try {
-            result->result = ft->ft->getSubwordId(std::string(word));
-        } catch (const std::exception& e) {
-            result->error = e.what();
+            _checkLoaded(ft);
+            result.result = ft->ft->getSubwordId(std::string(word));
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
+    }
+
+    SubwordsResult fasttext_get_subwords(const fasttext_t* ft, const char* word, size_t* n_subwords) {
+        SubwordsResult result = {};
+        try {
+            _checkLoaded(ft);
+            std::vector<int32_t> ngrams;
+            std::vector<std::string> substrings;
+            ft->ft->getDictionary()->getSubwords(std::string(word), ngrams, substrings);
+
+            *n_subwords = ngrams.size();
+            auto* c_subwords = new fasttext_subword_t[*n_subwords];
+            for (size_t i = 0; i < *n_subwords; ++i) {
+                c_subwords[i].subword = _copyString(substrings[i]);
+                c_subwords[i].id = ngrams[i];
+            }
+            result.result = c_subwords;
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    void fasttext_free_subwords(const fasttext_subword_t* subwords, const size_t n_subwords) {
+        for (size_t i = 0; i < n_subwords; ++i) {
+            delete[] subwords[i].subword;
+        }
+        delete[] subwords;
+    }
+
+    NgramVectorsResult fasttext_get_ngram_vectors(const fasttext_t* ft, const char* word, size_t* n_ngrams) {
+        NgramVectorsResult result = {};
+        try {
+            _checkLoaded(ft);
+            const auto ngrams = ft->ft->getNgramVectors(std::string(word));
+
+            *n_ngrams = ngrams.size();
+            auto* c_ngrams = new fasttext_ngram_vector_t[*n_ngrams];
+            for (size_t i = 0; i < *n_ngrams; ++i) {
+                const fasttext::Vector& v = ngrams[i].second;
+                c_ngrams[i].ngram = _copyString(ngrams[i].first);
+                c_ngrams[i].vector = new float[v.size()];
+                std::copy(v.data(), v.data() + v.size(), c_ngrams[i].vector);
+            }
+            result.result = c_ngrams;
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    void fasttext_free_ngram_vectors(const fasttext_ngram_vector_t* ngrams, const size_t n_ngrams) {
+        for (size_t i = 0; i < n_ngrams; ++i) {
+            delete[] ngrams[i].ngram;
+            delete[] ngrams[i].vector;
+        }
+        delete[] ngrams;
     }
 
     VoidResult fasttext_save_model(const fasttext_t* ft, const char* path) {
-        const auto result = new VoidResult();
+        VoidResult result = {};
         try {
-            ft->ft->saveModel(std::string(path));
-        } catch (const std::exception& e) {
-            result->error = e.what();
+            _checkLoaded(ft);
+            std::ofstream ofs(path, std::ofstream::binary);
+            if (!ofs.is_open()) {
+                throw CApiError(FASTTEXT_ERROR_IO, std::string(path) + " cannot be opened for saving!");
+            }
+            ft->ft->saveModel(ofs);
+            ofs.close();
+            if (ofs.fail()) {
+                throw CApiError(FASTTEXT_ERROR_IO, std::string(path) + " could not be written!");
+            }
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    /// Hands the bytes written to it over to a `fasttext_write_callback_t` in chunks.
+    class CallbackWriteBuffer : public std::streambuf {
+    public:
+        CallbackWriteBuffer(const fasttext_write_callback_t write, void* user_data)
+            : write_(write), user_data_(user_data), buffer_(1 << 16) {
+            setp(buffer_.data(), buffer_.data() + buffer_.size());
+        }
+
+        bool failed() const {
+            return failed_;
+        }
+
+    protected:
+        int_type overflow(const int_type c) override {
+            if (!flush()) {
+                return traits_type::eof();
+            }
+            if (c != traits_type::eof()) {
+                *pptr() = traits_type::to_char_type(c);
+                pbump(1);
+            }
+            return traits_type::not_eof(c);
+        }
+
+        int sync() override {
+            return flush() ? 0 : -1;
+        }
+
+    private:
+        bool flush() {
+            const std::ptrdiff_t n = pptr() - pbase();
+            if (failed_ || (n > 0 && !write_(pbase(), n, user_data_))) {
+                failed_ = true;
+                return false;
+            }
+            setp(buffer_.data(), buffer_.data() + buffer_.size());
+            return true;
+        }
+
+        fasttext_write_callback_t write_;
+        void* user_data_;
+        std::vector<char> buffer_;
+        bool failed_ = false;
+    };
+
+    VoidResult fasttext_save_model_to_writer(const fasttext_t* ft, const fasttext_write_callback_t write, void* user_data) {
+        VoidResult result = {};
+        try {
+            _checkLoaded(ft);
+            CallbackWriteBuffer buffer(write, user_data);
+            std::ostream out(&buffer);
+            ft->ft->saveModel(out);
+            out.flush();
+            if (buffer.failed()) {
+                throw CApiError(FASTTEXT_ERROR_IO, "Failed to write the model!");
+            }
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    VoidResult fasttext_save_output(const fasttext_t* ft, const char* path) {
+        VoidResult result = {};
+        try {
+            _checkLoaded(ft);
+            _checkNotQuantized(ft);
+            // `saveOutput` reports a file it can't open like any other exception.
+            if (!std::ofstream(path).is_open()) {
+                throw CApiError(FASTTEXT_ERROR_IO, std::string(path) + " cannot be opened for saving vectors!");
+            }
+            ft->ft->saveOutput(path);
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
     }
 
     IntResult fasttext_get_dimension(const fasttext_t* ft) {
-        const auto result = new IntResult();
+        IntResult result = {};
         try {
-            result->result = ft->ft->getDimension();
-        } catch (const std::exception& e) {
-            result->error = e.what();
+            _checkLoaded(ft);
+            result.result = ft->ft->getDimension();
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    fasttext_matrix_t _viewMatrix(const fasttext::DenseMatrix& matrix) {
+        return {matrix.data(), matrix.rows(), matrix.cols()};
+    }
+
+    MatrixResult fasttext_get_input_matrix(const fasttext_t* ft) {
+        MatrixResult result = {};
+        try {
+            _checkLoaded(ft);
+            _checkNotQuantized(ft);
+            result.result = _viewMatrix(*ft->ft->getInputMatrix());
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    MatrixResult fasttext_get_output_matrix(const fasttext_t* ft) {
+        MatrixResult result = {};
+        try {
+            _checkLoaded(ft);
+            // Only models quantized with `qout` have a quantized output matrix.
+            if (ft->ft->getArgs().qout) {
+                _checkNotQuantized(ft);
+            }
+            result.result = _viewMatrix(*ft->ft->getOutputMatrix());
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    /// Counts the bytes written to it and throws them away.
+    class CountingBuffer : public std::streambuf {
+    public:
+        int64_t count = 0;
+
+    protected:
+        std::streamsize xsputn(const char*, const std::streamsize n) override {
+            count += n;
+            return n;
+        }
+
+        int overflow(const int c) override {
+            if (c != traits_type::eof()) {
+                ++count;
+            }
+            return traits_type::not_eof(c);
+        }
+    };
+
+    Int64Result fasttext_get_model_size(const fasttext_t* ft) {
+        Int64Result result = {};
+        try {
+            _checkLoaded(ft);
+            CountingBuffer counter;
+            std::ostream out(&counter);
+            ft->ft->saveModel(out);
+            result.result = counter.count;
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    StringResult fasttext_get_metadata(const fasttext_t* ft) {
+        StringResult result = {};
+        try {
+            _checkLoaded(ft);
+            result.result = _copyString(ft->ft->getMetadata());
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    VoidResult fasttext_set_metadata(const fasttext_t* ft, const char* metadata) {
+        VoidResult result = {};
+        try {
+            _checkLoaded(ft);
+            try {
+                ft->ft->setMetadata(std::string(metadata));
+            } catch (const std::invalid_argument& e) {
+                throw CApiError(FASTTEXT_ERROR_INVALID_ARGUMENT, e.what());
+            }
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    void fasttext_free_string(const char* str) {
+        delete[] str;
+    }
+
+    Int32Result fasttext_get_nwords(const fasttext_t* ft) {
+        Int32Result result = {};
+        try {
+            _checkLoaded(ft);
+            result.result = ft->ft->getDictionary()->nwords();
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
+    }
+
+    Int32Result fasttext_get_nlabels(const fasttext_t* ft) {
+        Int32Result result = {};
+        try {
+            _checkLoaded(ft);
+            result.result = ft->ft->getDictionary()->nlabels();
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    Int64Result fasttext_get_ntokens(const fasttext_t* ft) {
+        Int64Result result = {};
+        try {
+            _checkLoaded(ft);
+            result.result = ft->ft->getDictionary()->ntokens();
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    /// Copies the words or the labels of the dictionary, with their counts.
+    TokensResult _getTokens(
+        const fasttext_t* ft,
+        std::string (fasttext::Dictionary::*getToken)(int32_t) const,
+        const fasttext::entry_type type,
+        size_t* n_tokens
+    ) {
+        TokensResult result = {};
+        try {
+            _checkLoaded(ft);
+            const auto dict = ft->ft->getDictionary();
+            const std::vector<int64_t> counts = dict->getCounts(type);
+
+            *n_tokens = counts.size();
+            auto* c_tokens = new fasttext_token_t[*n_tokens];
+            for (size_t i = 0; i < *n_tokens; ++i) {
+                c_tokens[i].token = _copyString(((*dict).*getToken)(static_cast<int32_t>(i)));
+                c_tokens[i].count = counts[i];
+            }
+            result.result = c_tokens;
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    TokensResult fasttext_get_words(const fasttext_t* ft, size_t* n_words) {
+        return _getTokens(ft, &fasttext::Dictionary::getWord, fasttext::entry_type::word, n_words);
+    }
+
+    TokensResult fasttext_get_labels(const fasttext_t* ft, size_t* n_labels) {
+        return _getTokens(ft, &fasttext::Dictionary::getLabel, fasttext::entry_type::label, n_labels);
+    }
+
+    void fasttext_free_tokens(const fasttext_token_t* tokens, const size_t n_tokens) {
+        for (size_t i = 0; i < n_tokens; ++i) {
+            delete[] tokens[i].token;
+        }
+        delete[] tokens;
     }
 
     VoidResult fasttext_get_word_vector(const fasttext_t* ft, const char* word, float* vec) {
-        const auto result = new VoidResult();
+        VoidResult result = {};
         try {
+            _checkLoaded(ft);
             fasttext::Vector v(ft->ft->getDimension());
             ft->ft->getWordVector(v, std::string(word));
             for (int i = 0; i < v.size(); ++i) {
                 vec[i] = v[i];
             }
-        } catch (const std::exception& e) {
-            result->error = e.what();
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
     }
 
     VoidResult fasttext_get_sentence_vector(const fasttext_t* ft, const char* text, float* vec) {
-        const auto result = new VoidResult();
+        VoidResult result = {};
         try {
+            _checkLoaded(ft);
             fasttext::Vector v(ft->ft->getDimension());
             std::stringstream ss(text);
             ft->ft->getSentenceVector(ss, v);
             for (int i = 0; i < v.size(); ++i) {
                 vec[i] = v[i];
             }
-        } catch (const std::exception& e) {
-            result->error = e.what();
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
     }
 
     FastTextPredictionResult fasttext_predict(const fasttext_t* ft, const char* text, const int32_t k, const float threshold, size_t* n_predictions) {
-        const auto result = new FastTextPredictionResult();
+        FastTextPredictionResult result = {};
         try {
+            _checkSupervised(ft);
             std::stringstream ss(text);
             std::vector<std::pair<fasttext::real, std::string>> predictions;
             ft->ft->predictLine(ss, predictions, k, threshold);
@@ -326,13 +821,11 @@ extern "C" {
                 strcpy(c_predictions[i].label, predictions[i].second.c_str());
             }
 
-            result->result = c_predictions;
-        } catch (const std::exception& e) {
-            result->error = e.what();
+            result.result = c_predictions;
         } catch (...) {
-            result->error = "An unknown exception occurred!";
+            _setError(result);
         }
-        return *result;
+        return result;
     }
 
     void fasttext_free_predictions(const fasttext_prediction_t* predictions, const size_t n_predictions) {
@@ -342,6 +835,191 @@ extern "C" {
         delete[] predictions;
     }
 
+    void _predictInto(const fasttext_t* ft, const char* text, const int32_t k, const float threshold, fasttext_predictions_t& out) {
+        std::stringstream ss(text);
+        std::vector<std::pair<fasttext::real, std::string>> predictions;
+        ft->ft->predictLine(ss, predictions, k, threshold);
+
+        out.n_predictions = predictions.size();
+        out.predictions = new fasttext_prediction_t[out.n_predictions];
+        for (size_t i = 0; i < out.n_predictions; ++i) {
+            out.predictions[i].probability = predictions[i].first;
+            out.predictions[i].label = _copyString(predictions[i].second);
+        }
+    }
+
+    FastTextPredictionsResult fasttext_predict_batch(
+        const fasttext_t* ft,
+        const char* const* texts,
+        const size_t n_texts,
+        const int32_t k,
+        const float threshold,
+        const int32_t n_threads
+    ) {
+        FastTextPredictionsResult result = {};
+        fasttext_predictions_t* batch = nullptr;
+        try {
+            _checkSupervised(ft);
+            if (n_threads < 1) {
+                throw std::invalid_argument("The number of threads must be positive!");
+            }
+            // Value-initialized so that a partially filled batch can always be freed.
+            batch = new fasttext_predictions_t[n_texts]();
+
+            // Prediction is `const`, so the model is shared as is. Each thread
+            // takes a contiguous chunk and writes to its own slots of the batch.
+            // There is no point in more threads than cores, or than texts.
+            const size_t cores = std::max(std::thread::hardware_concurrency(), 1u);
+            const size_t workers = std::min({static_cast<size_t>(n_threads), cores, std::max<size_t>(n_texts, 1)});
+            const size_t chunk = (n_texts + workers - 1) / workers;
+            std::vector<std::exception_ptr> errors(workers);
+            std::vector<std::thread> threads;
+            try {
+                threads.reserve(workers - 1);
+                for (size_t w = 1; w < workers; ++w) {
+                    threads.emplace_back([&, w]() {
+                        try {
+                            for (size_t i = w * chunk; i < std::min(n_texts, (w + 1) * chunk); ++i) {
+                                _predictInto(ft, texts[i], k, threshold, batch[i]);
+                            }
+                        } catch (...) {
+                            errors[w] = std::current_exception();
+                        }
+                    });
+                }
+            } catch (...) {
+                // The threads already started write into `batch`, so they have
+                // to be done before it is freed.
+                for (auto& thread : threads) {
+                    thread.join();
+                }
+                throw;
+            }
+            try {
+                for (size_t i = 0; i < std::min(n_texts, chunk); ++i) {
+                    _predictInto(ft, texts[i], k, threshold, batch[i]);
+                }
+            } catch (...) {
+                errors[0] = std::current_exception();
+            }
+            for (auto& thread : threads) {
+                thread.join();
+            }
+            for (const auto& error : errors) {
+                if (error) {
+                    std::rethrow_exception(error);
+                }
+            }
+
+            result.result = batch;
+        } catch (...) {
+            if (batch) {
+                fasttext_free_predictions_batch(batch, n_texts);
+            }
+            _setError(result);
+        }
+        return result;
+    }
+
+    void fasttext_free_predictions_batch(const fasttext_predictions_t* batch, const size_t n_texts) {
+        for (size_t i = 0; i < n_texts; ++i) {
+            fasttext_free_predictions(batch[i].predictions, batch[i].n_predictions);
+        }
+        delete[] batch;
+    }
+
+    TokenContributionsResult fasttext_explain(const fasttext_t* ft, const char* text, const char* label, size_t* n_contributions) {
+        TokenContributionsResult result = {};
+        try {
+            _checkSupervised(ft);
+            const fasttext::Args args = ft->ft->getArgs();
+            // With a hierarchical softmax a label has no row of its own to score against.
+            if (args.loss == fasttext::loss_name::hs) {
+                throw CApiError(FASTTEXT_ERROR_INVALID_ARGUMENT, "Can't explain a model trained with hierarchical softmax!");
+            }
+            const int32_t labelId = ft->ft->getLabelId(label);
+            if (labelId < 0) {
+                throw CApiError(FASTTEXT_ERROR_INVALID_ARGUMENT, std::string(label) + " is not a label of the model!");
+            }
+
+            // Reads the line the same way as `Dictionary::getLine`, but keeps
+            // track of the token each row of the input matrix comes from.
+            const auto dict = ft->ft->getDictionary();
+            std::vector<std::string> tokens;
+            std::vector<int32_t> kinds;
+            std::vector<std::vector<int32_t>> rows;
+            std::vector<std::string> words;
+            std::vector<int32_t> hashes;
+            std::stringstream ss(text);
+            std::string token;
+            while (dict->readWord(ss, token)) {
+                const uint32_t h = dict->hash(token);
+                const int32_t wid = dict->getId(token, h);
+                const fasttext::entry_type type = wid < 0 ? dict->getType(token) : dict->getType(wid);
+                if (type == fasttext::entry_type::word) {
+                    // A newline ends the text as the `</s>` word, which the
+                    // prediction counts like any other.
+                    tokens.push_back(token);
+                    kinds.push_back(token == fasttext::Dictionary::EOS ? FASTTEXT_NGRAM_KIND_EOS : FASTTEXT_NGRAM_KIND_WORD);
+                    rows.push_back(dict->getSubwords(token));
+                    words.push_back(token);
+                    hashes.push_back(h);
+                }
+                if (token == fasttext::Dictionary::EOS) {
+                    break;
+                }
+            }
+            for (size_t i = 0; i < hashes.size(); ++i) {
+                std::string ngram = words[i];
+                for (size_t j = i + 1; j < hashes.size() && j < i + args.wordNgrams; ++j) {
+                    ngram += " " + words[j];
+                    const int32_t id = dict->getWordNgramId(std::vector<int32_t>(hashes.begin() + i, hashes.begin() + j + 1));
+                    if (id >= 0) {
+                        tokens.push_back(ngram);
+                        kinds.push_back(FASTTEXT_NGRAM_KIND_WORD_NGRAM);
+                        rows.push_back({id});
+                    }
+                }
+            }
+
+            // The hidden layer averages all rows, so the logit of the label
+            // splits into the dot product of each row with its output row.
+            size_t n_rows = 0;
+            for (const auto& r : rows) {
+                n_rows += r.size();
+            }
+            fasttext::Vector output(args.dim);
+            fasttext::Vector input(args.dim);
+            ft->ft->getOutputVector(output, labelId);
+
+            *n_contributions = tokens.size();
+            auto* c_contributions = new fasttext_token_contribution_t[*n_contributions];
+            for (size_t i = 0; i < *n_contributions; ++i) {
+                float score = 0.0;
+                for (const int32_t row : rows[i]) {
+                    ft->ft->getInputVector(input, row);
+                    for (int64_t d = 0; d < input.size(); ++d) {
+                        score += input[d] * output[d];
+                    }
+                }
+                c_contributions[i].token = _copyString(tokens[i]);
+                c_contributions[i].ngram_kind = kinds[i];
+                c_contributions[i].score = n_rows > 0 ? score / n_rows : 0.0;
+            }
+            result.result = c_contributions;
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    void fasttext_free_token_contributions(const fasttext_token_contribution_t* contributions, const size_t n_contributions) {
+        for (size_t i = 0; i < n_contributions; ++i) {
+            delete[] contributions[i].token;
+        }
+        delete[] contributions;
+    }
+
     fasttext::model_name _stringToModel(const std::string& mn) {
       // Remember this is ok because this is C++, otherwise, strcmp should be used
       if (mn == "cbow") {
@@ -369,4 +1047,231 @@ extern "C" {
         ft.train(args);
     }
 
+    void _checkTrainingInput(const fasttext::Args& args) {
+        _checkReadable(args.input, "training");
+        if (!args.pretrainedVectors.empty()) {
+            _checkReadable(args.pretrainedVectors, "loading pretrained vectors");
+        }
+    }
+
+    VoidResult fasttext_train_with_args(const fasttext_t* ft, const fasttext_args_t* args) {
+        VoidResult result = {};
+        try {
+            _checkTrainingInput(*args->args);
+            ft->ft->train(*args->args);
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    VoidResult fasttext_quantize(const fasttext_t* ft, const fasttext_args_t* qargs) {
+        VoidResult result = {};
+        try {
+            _checkSupervised(ft);
+            _checkNotQuantized(ft);
+            if (qargs->args->retrain && qargs->args->cutoff > 0) {
+                // A training thread would spin forever on a file it can't read.
+                _checkReadable(qargs->args->input, "training");
+            }
+            ft->ft->quantize(*qargs->args);
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    VoidResult fasttext_train_with_callback(
+        const fasttext_t* ft,
+        const fasttext_args_t* args,
+        const fasttext_train_callback_t callback,
+        void* user_data
+    ) {
+        VoidResult result = {};
+        try {
+            _checkTrainingInput(*args->args);
+            // The callback runs on every training thread, only abort once.
+            std::atomic<bool> aborted(false);
+            ft->ft->train(*args->args, [&](float progress, float loss, double wst, double lr, int64_t eta) {
+                if (!callback(progress, loss, wst, lr, eta, user_data) && !aborted.exchange(true)) {
+                    ft->ft->abort();
+                }
+            });
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    ArgsResult fasttext_get_args(const fasttext_t* ft) {
+        ArgsResult result = {};
+        try {
+            _checkLoaded(ft);
+            auto* c_args = new fasttext_args_t();
+            c_args->args = new fasttext::Args(ft->ft->getArgs());
+            result.result = c_args;
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    BoolResult fasttext_is_quant(const fasttext_t* ft) {
+        BoolResult result = {};
+        try {
+            _checkLoaded(ft);
+            result.result = ft->ft->isQuant();
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    AutotuneResult fasttext_autotune(
+        const fasttext_t* ft,
+        const fasttext_args_t* args,
+        const fasttext_autotune_callback_t callback,
+        void* user_data
+    ) {
+        AutotuneResult result = {};
+        try {
+            if (args->args->model != fasttext::model_name::sup) {
+                throw CApiError(FASTTEXT_ERROR_NOT_SUPERVISED, "Autotune only supports supervised models!");
+            }
+            _checkTrainingInput(*args->args);
+            _checkReadable(args->args->autotuneValidationFile, "validation");
+            // `ft` keeps ownership of the model, the autotune only borrows it.
+            const std::shared_ptr<fasttext::FastText> fastText(ft->ft, [](fasttext::FastText*) {});
+            fasttext::Autotune autotune(fastText);
+            autotune.train(*args->args, [&](int32_t trial, const fasttext::Args& trialArgs, double score) {
+                fasttext_args_t c_args = { const_cast<fasttext::Args*>(&trialArgs) };
+                return callback == nullptr || callback(trial, &c_args, score, user_data);
+            });
+            result.result.best_score = autotune.getBestScore();
+            result.result.trials = autotune.getTrials();
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    /* --- Evaluation --- */
+
+    struct fasttext_meter_t {
+        fasttext::Meter* meter;
+        std::vector<std::string> labels;
+    };
+
+    fasttext_meter_t* _test(const fasttext_t* ft, std::istream& in, const int32_t k, const float threshold) {
+        _checkSupervised(ft);
+        const auto dict = ft->ft->getDictionary();
+
+        // Logging the gold labels the model missed makes the recall reachable
+        // from the scores, which is what precision-recall curves need.
+        auto meter = std::unique_ptr<fasttext::Meter>(new fasttext::Meter(true));
+        ft->ft->test(in, k, threshold, *meter);
+
+        auto* c_meter = new fasttext_meter_t();
+        c_meter->meter = meter.release();
+        for (int32_t i = 0; i < dict->nlabels(); i++) {
+            c_meter->labels.push_back(dict->getLabel(i));
+        }
+        return c_meter;
+    }
+
+    MeterResult fasttext_test(const fasttext_t* ft, const char* path, const int32_t k, const float threshold) {
+        MeterResult result = {};
+        try {
+            _checkReadable(path, "testing");
+            std::ifstream ifs(path);
+            result.result = _test(ft, ifs, k, threshold);
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    MeterResult fasttext_test_from_reader(
+        const fasttext_t* ft,
+        const fasttext_read_callback_t read,
+        void* user_data,
+        const int32_t k,
+        const float threshold
+    ) {
+        MeterResult result = {};
+        try {
+            CallbackReadBuffer buffer(read, user_data);
+            std::istream in(&buffer);
+            auto* meter = _test(ft, in, k, threshold);
+            if (buffer.failed()) {
+                fasttext_meter_delete(meter);
+                throw CApiError(FASTTEXT_ERROR_IO, "Failed to read the test data!");
+            }
+            result.result = meter;
+        } catch (...) {
+            _setError(result);
+        }
+        return result;
+    }
+
+    void fasttext_meter_delete(const fasttext_meter_t* meter) {
+        delete meter->meter;
+        delete meter;
+    }
+
+    uint64_t fasttext_meter_nexamples(const fasttext_meter_t* meter) {
+        return meter->meter->nexamples();
+    }
+
+    double fasttext_meter_precision(const fasttext_meter_t* meter) {
+        return meter->meter->precision();
+    }
+
+    double fasttext_meter_recall(const fasttext_meter_t* meter) {
+        return meter->meter->recall();
+    }
+
+    double fasttext_meter_f1_score(const fasttext_meter_t* meter) {
+        return meter->meter->f1Score();
+    }
+
+    int32_t fasttext_meter_nlabels(const fasttext_meter_t* meter) {
+        return static_cast<int32_t>(meter->labels.size());
+    }
+
+    const char* fasttext_meter_label(const fasttext_meter_t* meter, const int32_t label_id) {
+        return meter->labels[label_id].c_str();
+    }
+
+    double fasttext_meter_label_precision(const fasttext_meter_t* meter, const int32_t label_id) {
+        return meter->meter->precision(label_id);
+    }
+
+    double fasttext_meter_label_recall(const fasttext_meter_t* meter, const int32_t label_id) {
+        return meter->meter->recall(label_id);
+    }
+
+    double fasttext_meter_label_f1_score(const fasttext_meter_t* meter, const int32_t label_id) {
+        return meter->meter->f1Score(label_id);
+    }
+
+    fasttext_score_vs_true_t* fasttext_meter_score_vs_true(
+        const fasttext_meter_t* meter,
+        const int32_t label_id,
+        size_t* n_scores
+    ) {
+        const auto scores = meter->meter->scoreVsTrue(label_id);
+        *n_scores = scores.size();
+        auto* c_scores = new fasttext_score_vs_true_t[*n_scores];
+        for (size_t i = 0; i < *n_scores; ++i) {
+            c_scores[i].score = scores[i].first;
+            c_scores[i].gold = scores[i].second;
+        }
+        return c_scores;
+    }
+
+    void fasttext_free_score_vs_true(const fasttext_score_vs_true_t* scores) {
+        delete[] scores;
+    }
+
 }
@@ -15,12 +15,25 @@ extern "C" {
         char* second;
     } Result;
 
+    /// What went wrong when the `error` of a result is set.
+    #define FASTTEXT_ERROR_CPP 1
+    #define FASTTEXT_ERROR_INVALID_MODEL_FORMAT 2
+    #define FASTTEXT_ERROR_IO 3
+    #define FASTTEXT_ERROR_MODEL_NOT_LOADED 4
+    #define FASTTEXT_ERROR_NOT_SUPERVISED 5
+    #define FASTTEXT_ERROR_QUANTIZED_UNSUPPORTED 6
+    #define FASTTEXT_ERROR_INVALID_ARGUMENT 7
+    #define FASTTEXT_ERROR_NOT_MAPPABLE 8
+
     /// The result of an FFI call so that an error can properly cross the FFI-boundary
     /// This is a macro so that we can have generics:
     /// https://iafisher.com/blog/2020/06/type-safe-generics-in-c
+    ///
+    /// A non-null `error` is owned by the caller and must be released with `fasttext_free_error`.
     #define DEFINE_RESULT(typename, type) \
     typedef struct typename##Result { \
         const char* error; \
+        uint32_t error_code; \
         type result; \
     } typename##Result; \
     // \
@@ -32,10 +45,13 @@ extern "C" {
     typedef struct fasttext_args_t fasttext_args_t;
     typedef struct fasttext_t fasttext_t;
 
+    DEFINE_RESULT(Void, void*);
+    void fasttext_free_error(const char* error);
+
     fasttext_args_t* fasttext_args_new();
     void fasttext_args_delete(const fasttext_args_t* args);
 
-    void fasttext_args_parse(const fasttext_args_t* args, int argc, char** argv);
+    VoidResult fasttext_args_parse(const fasttext_args_t* args, int argc, char** argv);
 
     const char* fasttext_args_get_input(const fasttext_args_t* args);
     void fasttext_args_set_input(const fasttext_args_t* args, const char* input);
@@ -103,6 +119,9 @@ extern "C" {
     int fasttext_args_get_save_output(const fasttext_args_t* args);
     void fasttext_args_set_save_output(const fasttext_args_t* args, int save_output);
 
+    int fasttext_args_get_seed(const fasttext_args_t* args);
+    void fasttext_args_set_seed(const fasttext_args_t* args, int seed);
+
     int fasttext_args_get_qout(const fasttext_args_t* args);
     void fasttext_args_set_qout(const fasttext_args_t* args, int qout);
 
@@ -118,10 +137,29 @@ extern "C" {
     size_t fasttext_args_get_dsub(const fasttext_args_t* args);
     void fasttext_args_set_dsub(const fasttext_args_t* args, size_t dsub);
 
+    const char* fasttext_args_get_autotune_validation_file(const fasttext_args_t* args);
+    void fasttext_args_set_autotune_validation_file(const fasttext_args_t* args, const char* validation_file);
+
+    const char* fasttext_args_get_autotune_metric(const fasttext_args_t* args);
+    void fasttext_args_set_autotune_metric(const fasttext_args_t* args, const char* metric);
+
+    int fasttext_args_get_autotune_predictions(const fasttext_args_t* args);
+    void fasttext_args_set_autotune_predictions(const fasttext_args_t* args, int predictions);
+
+    int fasttext_args_get_autotune_duration(const fasttext_args_t* args);
+    void fasttext_args_set_autotune_duration(const fasttext_args_t* args, int duration);
+
+    const char* fasttext_args_get_autotune_model_size(const fasttext_args_t* args);
+    void fasttext_args_set_autotune_model_size(const fasttext_args_t* args, const char* model_size);
+
+    /// Whether an argument, named as on the command line without the dash, was set explicitly.
+    /// Autotune leaves those untouched.
+    bool fasttext_args_is_manual(const fasttext_args_t* args, const char* name);
+    void fasttext_args_set_manual(const fasttext_args_t* args, const char* name);
+
     /* --- FastText --- */
 
     DEFINE_RESULT(FastText, fasttext_t*);
-    DEFINE_RESULT(Void, void*);
 
     FastTextResult fasttext_new();
     void fasttext_delete(const fasttext_t* ft);
@@ -129,6 +167,16 @@ extern "C" {
     VoidResult fasttext_load_model(const fasttext_t* ft, const char* path);
     VoidResult fasttext_load_model_from_buffer(const fasttext_t* ft, const void* data, size_t size);
 
+    /// Fills `buffer` with up to `size` bytes and returns how many were written,
+    /// 0 at the end of the input or a negative value on error.
+    typedef int64_t (*fasttext_read_callback_t)(char* buffer, size_t size, void* user_data);
+
+    VoidResult fasttext_load_model_from_reader(const fasttext_t* ft, fasttext_read_callback_t read, void* user_data);
+
+    /// Loads the input matrix straight from a private mapping of the file instead
+    /// of copying it, so that its pages are only read when used.
+    VoidResult fasttext_load_model_mmap(const fasttext_t* ft, const char* path);
+
     typedef struct fasttext_float_char_pair_t {
         float first;
         char* second;
@@ -149,11 +197,77 @@ extern "C" {
     Int32Result fasttext_get_word_id(const fasttext_t *ft, const char *word);
     Int32Result fasttext_get_subword_id(const fasttext_t *ft, const char *word);
 
+    /// A subword of a word and its row in the input matrix.
+    typedef struct fasttext_subword_t {
+        char* subword;
+        int32_t id;
+    } fasttext_subword_t;
+    DEFINE_RESULT(Subwords, fasttext_subword_t*);
+
+    SubwordsResult fasttext_get_subwords(const fasttext_t* ft, const char* word, size_t* n_subwords);
+    void fasttext_free_subwords(const fasttext_subword_t* subwords, size_t n_subwords);
+
+    /// A subword of a word and its vector, which has the dimension of the model.
+    typedef struct fasttext_ngram_vector_t {
+        char* ngram;
+        float* vector;
+    } fasttext_ngram_vector_t;
+    DEFINE_RESULT(NgramVectors, fasttext_ngram_vector_t*);
+
+    NgramVectorsResult fasttext_get_ngram_vectors(const fasttext_t* ft, const char* word, size_t* n_ngrams);
+    void fasttext_free_ngram_vectors(const fasttext_ngram_vector_t* ngrams, size_t n_ngrams);
+
     VoidResult fasttext_save_model(const fasttext_t* ft, const char* path);
 
+    /// Writes all `size` bytes of `buffer`, returns false on error.
+    typedef bool (*fasttext_write_callback_t)(const char* buffer, size_t size, void* user_data);
+
+    VoidResult fasttext_save_model_to_writer(const fasttext_t* ft, fasttext_write_callback_t write, void* user_data);
+    VoidResult fasttext_save_output(const fasttext_t* ft, const char* path);
+
     DEFINE_RESULT(Int, int);
     IntResult fasttext_get_dimension(const fasttext_t* ft);
 
+    /// A dense matrix of the model, stored row after row.
+    ///
+    /// `data` is owned by the model and only valid until another model is
+    /// loaded, trained or quantized in its place.
+    typedef struct fasttext_matrix_t {
+        const float* data;
+        int64_t rows;
+        int64_t cols;
+    } fasttext_matrix_t;
+    DEFINE_RESULT(Matrix, fasttext_matrix_t);
+
+    MatrixResult fasttext_get_input_matrix(const fasttext_t* ft);
+    MatrixResult fasttext_get_output_matrix(const fasttext_t* ft);
+
+    DEFINE_RESULT(Int64, int64_t);
+    Int64Result fasttext_get_model_size(const fasttext_t* ft);
+
+    /// Free-form text saved at the end of the model file, empty when there is none.
+    ///
+    /// The returned string is owned by the caller and must be released with `fasttext_free_string`.
+    DEFINE_RESULT(String, char*);
+    StringResult fasttext_get_metadata(const fasttext_t* ft);
+    VoidResult fasttext_set_metadata(const fasttext_t* ft, const char* metadata);
+    void fasttext_free_string(const char* str);
+
+    Int32Result fasttext_get_nwords(const fasttext_t* ft);
+    Int32Result fasttext_get_nlabels(const fasttext_t* ft);
+    Int64Result fasttext_get_ntokens(const fasttext_t* ft);
+
+    /// An entry of the dictionary and the number of times it was seen in training.
+    typedef struct fasttext_token_t {
+        char* token;
+        int64_t count;
+    } fasttext_token_t;
+    DEFINE_RESULT(Tokens, fasttext_token_t*);
+
+    TokensResult fasttext_get_words(const fasttext_t* ft, size_t* n_words);
+    TokensResult fasttext_get_labels(const fasttext_t* ft, size_t* n_labels);
+    void fasttext_free_tokens(const fasttext_token_t* tokens, size_t n_tokens);
+
     VoidResult fasttext_get_word_vector(const fasttext_t* ft, const char* word, float* vec);
     VoidResult fasttext_get_sentence_vector(const fasttext_t* ft, const char* text, float* vec);
 
@@ -166,7 +280,110 @@ extern "C" {
     FastTextPredictionResult fasttext_predict(const fasttext_t* ft, const char* text, int32_t k, float threshold, size_t* n_predictions);
     void fasttext_free_predictions(const fasttext_prediction_t* predictions, size_t n_predictions);
 
+    /// The predictions for one of the texts of a batch.
+    typedef struct fasttext_predictions_t {
+        fasttext_prediction_t* predictions;
+        size_t n_predictions;
+    } fasttext_predictions_t;
+    DEFINE_RESULT(FastTextPredictions, fasttext_predictions_t*);
+
+    FastTextPredictionsResult fasttext_predict_batch(
+        const fasttext_t* ft,
+        const char* const* texts,
+        size_t n_texts,
+        int32_t k,
+        float threshold,
+        int32_t n_threads
+    );
+    void fasttext_free_predictions_batch(const fasttext_predictions_t* batch, size_t n_texts);
+
+    /// What a token contribution of `fasttext_explain` stands for.
+    #define FASTTEXT_NGRAM_KIND_WORD 0
+    #define FASTTEXT_NGRAM_KIND_WORD_NGRAM 1
+    #define FASTTEXT_NGRAM_KIND_EOS 2
+
+    /// The share of a word or word n-gram in the score of a label.
+    typedef struct fasttext_token_contribution_t {
+        char* token;
+        int32_t ngram_kind;
+        float score;
+    } fasttext_token_contribution_t;
+    DEFINE_RESULT(TokenContributions, fasttext_token_contribution_t*);
+
+    TokenContributionsResult fasttext_explain(const fasttext_t* ft, const char* text, const char* label, size_t* n_contributions);
+    void fasttext_free_token_contributions(const fasttext_token_contribution_t* contributions, size_t n_contributions);
+
     void fasttext_train(const char* input, const char* output, const char* model_name, bool retrain, bool qout, int thread);
+    VoidResult fasttext_train_with_args(const fasttext_t* ft, const fasttext_args_t* args);
+    VoidResult fasttext_quantize(const fasttext_t* ft, const fasttext_args_t* qargs);
+
+    /// Called periodically from the training threads. Returning false aborts the training.
+    typedef bool (*fasttext_train_callback_t)(float progress, float loss, double wst, double lr, int64_t eta, void* user_data);
+    VoidResult fasttext_train_with_callback(
+        const fasttext_t* ft,
+        const fasttext_args_t* args,
+        fasttext_train_callback_t callback,
+        void* user_data
+    );
+
+    DEFINE_RESULT(Args, fasttext_args_t*);
+    ArgsResult fasttext_get_args(const fasttext_t* ft);
+
+    DEFINE_RESULT(Bool, bool);
+    BoolResult fasttext_is_quant(const fasttext_t* ft);
+
+    /// Called after every autotune trial, the score is NaN if the trial failed.
+    /// Returning false stops the search.
+    typedef bool (*fasttext_autotune_callback_t)(int32_t trial, const fasttext_args_t* args, double score, void* user_data);
+    typedef struct fasttext_autotune_result_t {
+        double best_score;
+        int32_t trials;
+    } fasttext_autotune_result_t;
+    DEFINE_RESULT(Autotune, fasttext_autotune_result_t);
+    AutotuneResult fasttext_autotune(
+        const fasttext_t* ft,
+        const fasttext_args_t* args,
+        fasttext_autotune_callback_t callback,
+        void* user_data
+    );
+
+    /* --- Evaluation --- */
+
+    typedef struct fasttext_meter_t fasttext_meter_t;
+    DEFINE_RESULT(Meter, fasttext_meter_t*);
+
+    MeterResult fasttext_test(const fasttext_t* ft, const char* path, int32_t k, float threshold);
+    MeterResult fasttext_test_from_reader(
+        const fasttext_t* ft,
+        fasttext_read_callback_t read,
+        void* user_data,
+        int32_t k,
+        float threshold
+    );
+    void fasttext_meter_delete(const fasttext_meter_t* meter);
+
+    uint64_t fasttext_meter_nexamples(const fasttext_meter_t* meter);
+    double fasttext_meter_precision(const fasttext_meter_t* meter);
+    double fasttext_meter_recall(const fasttext_meter_t* meter);
+    double fasttext_meter_f1_score(const fasttext_meter_t* meter);
+
+    int32_t fasttext_meter_nlabels(const fasttext_meter_t* meter);
+    const char* fasttext_meter_label(const fasttext_meter_t* meter, int32_t label_id);
+    double fasttext_meter_label_precision(const fasttext_meter_t* meter, int32_t label_id);
+    double fasttext_meter_label_recall(const fasttext_meter_t* meter, int32_t label_id);
+    double fasttext_meter_label_f1_score(const fasttext_meter_t* meter, int32_t label_id);
+
+    /// A gold label the model didn't predict has a score of -1.
+    typedef struct fasttext_score_vs_true_t {
+        float score;
+        float gold;
+    } fasttext_score_vs_true_t;
+    fasttext_score_vs_true_t* fasttext_meter_score_vs_true(
+        const fasttext_meter_t* meter,
+        int32_t label_id,
+        size_t* n_scores
+    );
+    void fasttext_free_score_vs_true(const fasttext_score_vs_true_t* scores);
 
 #ifdef __cplusplus
 }
@@ -8,6 +8,8 @@
 
 #include "densematrix.h"
 
+#include <cerrno>
+#include <cstring>
 #include <random>
 #include <stdexcept>
 #include <thread>
@@ -15,6 +17,13 @@
 #include "utils.h"
 #include "vector.h"
 
+#ifndef _WIN32
+#include <fcntl.h>
+#include <sys/mman.h>
+#include <sys/stat.h>
+#include <unistd.h>
+#endif
+
 #if defined(__AVX512F__) || defined(__AVX__) || defined(__SSE__)
 #include <immintrin.h>
 #endif
@@ -26,11 +35,23 @@ DenseMatrix::DenseMatrix() : DenseMatrix(0, 0) {}
 DenseMatrix::DenseMatrix(int64_t m, int64_t n) : Matrix(m, n), data_(m * n) {}
 
 DenseMatrix::DenseMatrix(DenseMatrix&& other) noexcept
-    : Matrix(other.m_, other.n_), data_(std::move(other.data_)) {}
+    : Matrix(other.m_, other.n_),
+      data_(std::move(other.data_)),
+      mapping_(std::move(other.mapping_)) {}
 
 DenseMatrix::DenseMatrix(int64_t m, int64_t n, real* dataPtr)
     : Matrix(m, n), data_(dataPtr, dataPtr + (m * n)) {}
 
+void DenseMatrix::detach() {
+  if (!mapping_) {
+    return;
+  }
+  intgemm::AlignedVector<real> data(data_.size());
+  std::memcpy(data.data(), data_.data(), data_.size() * sizeof(real));
+  data_ = std::move(data);
+  mapping_.reset();
+}
+
 void DenseMatrix::zero() {
   std::fill(data_.begin(), data_.end(), 0.0);
 }
@@ -47,6 +68,9 @@ void DenseMatrix::uniformThread(real a, int block, int32_t seed) {
 }
 
 void DenseMatrix::uniform(real a, unsigned int thread, int32_t seed) {
+  // uniformThread only fills the blocks it is given, and unlike std::vector
+  // the AlignedVector storage starts out uninitialized.
+  zero();
   if (thread > 1) {
     std::vector<std::thread> threads;
     for (int i = 0; i < thread; i++) {
@@ -210,7 +234,9 @@ template <unsigned Cols> void averageRowsFast(Vector& x, const std::vector<int32
 
 void DenseMatrix::averageRowsToVector(Vector& x, const std::vector<int32_t>& rows) const {
 #if defined(__AVX512F__) || defined(__AVX__) || defined(__SSE__)
-  switch (cols()) {
+  // A memory-mapped matrix may not even be aligned for single values.
+  const bool aligned = reinterpret_cast<uintptr_t>(data()) % sizeof(Register) == 0;
+  switch (aligned ? cols() : 0) {
     case 512:
       // Maximum number that can fit all in registers on AVX512F.
       averageRowsFast<512>(x, rows, *this);
@@ -249,6 +275,33 @@ void DenseMatrix::load(std::istream& in) {
   in.read((char*)data_.data(), m_ * n_ * sizeof(real));
 }
 
+void DenseMatrix::load(
+    std::istream& in,
+    const std::shared_ptr<MappedFile>& file) {
+  in.read((char*)&m_, sizeof(int64_t));
+  in.read((char*)&n_, sizeof(int64_t));
+  const std::streamoff offset = in.tellg();
+  if (in && (m_ < 0 || n_ < 0)) {
+    throw std::invalid_argument(
+        "Invalid model file: negative input matrix dimensions.");
+  }
+  // Checked by division, since m_ * n_ * sizeof(real) can overflow.
+  if (!in || offset < 0 || static_cast<size_t>(offset) > file->size() ||
+      (n_ > 0 &&
+       static_cast<size_t>(m_) > (file->size() - offset) / sizeof(real) / n_)) {
+    throw std::invalid_argument("Model from mapped file is truncated!");
+  }
+  const int64_t bytes = m_ * n_ * sizeof(real);
+  const char* values = file->data() + offset;
+  // The mapping is read-only, so anything that writes to the matrix has to
+  // detach it first. The values are only aligned for floats when the
+  // dictionary before them happens to leave them so.
+  data_ = intgemm::AlignedVector<real>::borrow(
+      const_cast<real*>(reinterpret_cast<const real*>(values)), m_ * n_);
+  mapping_ = file;
+  in.seekg(bytes, std::ios_base::cur);
+}
+
 void DenseMatrix::dump(std::ostream& out) const {
   out << m_ << " " << n_ << std::endl;
   for (int64_t i = 0; i < m_; i++) {
@@ -262,4 +315,45 @@ void DenseMatrix::dump(std::ostream& out) const {
   }
 };
 
+MappedFile::MappedFile(const std::string& filename)
+    : data_(nullptr), size_(0) {
+#ifdef _WIN32
+  throw DenseMatrix::NotMappableError(
+      "models can't be memory-mapped on Windows, use load_model instead.");
+#else
+  int fd = open(filename.c_str(), O_RDONLY);
+  if (fd < 0) {
+    throw std::invalid_argument(filename + " cannot be opened for loading!");
+  }
+  struct stat st;
+  if (fstat(fd, &st) != 0) {
+    const int error = errno;
+    close(fd);
+    throw std::runtime_error(filename + ": " + std::strerror(error));
+  }
+  size_ = st.st_size;
+  if (size_ > 0) {
+    void* data = mmap(nullptr, size_, PROT_READ, MAP_PRIVATE, fd, 0);
+    const int error = errno;
+    close(fd);
+    if (data == MAP_FAILED) {
+      throw DenseMatrix::NotMappableError(
+          std::string("the model can't be memory-mapped (") +
+          std::strerror(error) + "), use load_model instead.");
+    }
+    data_ = static_cast<char*>(data);
+  } else {
+    close(fd);
+  }
+#endif
+}
+
+MappedFile::~MappedFile() {
+#ifndef _WIN32
+  if (data_) {
+    munmap(const_cast<char*>(data_), size_);
+  }
+#endif
+}
+
 } // namespace fasttext
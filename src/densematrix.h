@@ -10,9 +10,12 @@
 
 #include <assert.h>
 #include <cstdint>
+#include <cstring>
 #include <istream>
+#include <memory>
 #include <ostream>
 #include <stdexcept>
+#include <string>
 #include <vector>
 
 #include "aligned.h"
@@ -23,9 +26,32 @@ namespace fasttext {
 
 class Vector;
 
+// A model file mapped read-only in memory, so that its pages are shared
+// between processes.
+class MappedFile {
+ protected:
+  const char* data_;
+  size_t size_;
+
+ public:
+  explicit MappedFile(const std::string& filename);
+  MappedFile(const MappedFile&) = delete;
+  MappedFile& operator=(const MappedFile&) = delete;
+  ~MappedFile();
+
+  inline const char* data() const {
+    return data_;
+  }
+  inline size_t size() const {
+    return size_;
+  }
+};
+
 class DenseMatrix : public Matrix {
  protected:
   intgemm::AlignedVector<real> data_;
+  // Keeps the file alive when data_ points into it.
+  std::shared_ptr<MappedFile> mapping_;
   void uniformThread(real, int, int32_t);
 
  public:
@@ -45,9 +71,17 @@ class DenseMatrix : public Matrix {
     return data_.data();
   }
 
-  inline const real& at(int64_t i, int64_t j) const {
+  // A memory-mapped matrix may not be aligned for floats, so values are read
+  // byte by byte.
+  inline real at(int64_t i, int64_t j) const {
     assert(i * n_ + j < data_.size());
-    return data_[i * n_ + j];
+    real value;
+    std::memcpy(
+        &value,
+        reinterpret_cast<const char*>(data_.data()) +
+            (i * n_ + j) * sizeof(real),
+        sizeof(real));
+    return value;
   };
   inline real& at(int64_t i, int64_t j) {
     return data_[i * n_ + j];
@@ -59,6 +93,9 @@ class DenseMatrix : public Matrix {
   inline int64_t cols() const {
     return n_;
   }
+  // Copies a memory-mapped matrix into memory of its own, so that it can be
+  // written to.
+  void detach();
   void zero();
   void uniform(real, unsigned int, int32_t);
 
@@ -75,11 +112,18 @@ class DenseMatrix : public Matrix {
   void averageRowsToVector(Vector& x, const std::vector<int32_t>& rows) const override;
   void save(std::ostream&) const override;
   void load(std::istream&) override;
+  void load(std::istream&, const std::shared_ptr<MappedFile>&);
   void dump(std::ostream&) const override;
 
   class EncounteredNaNError : public std::runtime_error {
    public:
     EncounteredNaNError() : std::runtime_error("Encountered NaN.") {}
   };
+
+  class NotMappableError : public std::runtime_error {
+   public:
+    explicit NotMappableError(const std::string& message)
+        : std::runtime_error(message) {}
+  };
 };
 } // namespace fasttext
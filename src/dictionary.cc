@@ -185,8 +185,10 @@ void Dictionary::computeSubwords(
       }
       if (n >= args_->minn && !(n == 1 && (i == 0 || j == word.size()))) {
         int32_t h = hash(ngram) % args_->bucket;
+        const size_t size = ngrams.size();
         pushHash(ngrams, h);
-        if (substrings) {
+        // A pruned n-gram has no row, so its substring is dropped as well.
+        if (substrings && ngrams.size() > size) {
           substrings->push_back(ngram);
         }
       }
@@ -322,6 +324,23 @@ void Dictionary::addWordNgrams(
   }
 }
 
+int32_t Dictionary::getWordNgramId(const std::vector<int32_t>& hashes) const {
+  // Same hash as the n-gram spanning all of hashes in addWordNgrams.
+  std::vector<int32_t> line;
+  uint64_t h = hashes[0];
+  for (int32_t j = 1; j < hashes.size(); j++) {
+    h = h * 116049371 + hashes[j];
+  }
+  pushHash(line, h % args_->bucket);
+  return line.empty() ? -1 : line[0];
+}
+
+int32_t Dictionary::getSubwordId(const std::string_view subword) const {
+  std::vector<int32_t> line;
+  pushHash(line, hash(subword) % args_->bucket);
+  return line.empty() ? -1 : line[0];
+}
+
 void Dictionary::addSubwords(
     std::vector<int32_t>& line,
     const std::string_view token,
@@ -24,6 +24,8 @@ namespace fasttext {
 
 constexpr int32_t FASTTEXT_VERSION = 12; /* Version 1b */
 constexpr int32_t FASTTEXT_FILEFORMAT_MAGIC_INT32 = 793712314;
+constexpr int32_t FASTTEXT_METADATA_MAGIC_INT32 = 793712315;
+constexpr int64_t FASTTEXT_METADATA_MAX_SIZE = 1 << 20;
 
 bool comparePairs(
     const std::pair<real, std::string>& l,
@@ -91,13 +93,23 @@ std::shared_ptr<const DenseMatrix> FastText::getOutputMatrix() const {
   return std::dynamic_pointer_cast<DenseMatrix>(output_);
 }
 
+const std::string& FastText::getMetadata() const {
+  return metadata_;
+}
+
+void FastText::setMetadata(const std::string& metadata) {
+  if (metadata.size() > FASTTEXT_METADATA_MAX_SIZE) {
+    throw std::invalid_argument("Model metadata can't exceed 1 MiB!");
+  }
+  metadata_ = metadata;
+}
+
 int32_t FastText::getWordId(const std::string& word) const {
   return dict_->getId(word);
 }
 
 int32_t FastText::getSubwordId(const std::string& subword) const {
-  int32_t h = dict_->hash(subword) % args_->bucket;
-  return dict_->nwords() + h;
+  return dict_->getSubwordId(subword);
 }
 
 int32_t FastText::getLabelId(const std::string& label) const {
@@ -194,20 +206,57 @@ void FastText::saveModel(const std::string& filename) {
   if (!ofs.is_open()) {
     throw std::invalid_argument(filename + " cannot be opened for saving!");
   }
+  saveModel(ofs);
+  ofs.close();
+}
+
+void FastText::saveModel(std::ostream& out) {
   if (!input_ || !output_) {
     throw std::runtime_error("Model never trained");
   }
-  signModel(ofs);
-  args_->save(ofs);
-  dict_->save(ofs);
+  signModel(out);
+  args_->save(out);
+  dict_->save(out);
 
-  ofs.write((char*)&(quant_), sizeof(bool));
-  input_->save(ofs);
+  out.write((char*)&(quant_), sizeof(bool));
+  input_->save(out);
 
-  ofs.write((char*)&(args_->qout), sizeof(bool));
-  output_->save(ofs);
+  out.write((char*)&(args_->qout), sizeof(bool));
+  output_->save(out);
 
-  ofs.close();
+  // The metadata goes last, where loaders that don't know about it stop
+  // reading, so the file stays readable by any fastText.
+  if (!metadata_.empty()) {
+    const int32_t magic = FASTTEXT_METADATA_MAGIC_INT32;
+    const int64_t size = metadata_.size();
+    out.write((char*)&(magic), sizeof(int32_t));
+    out.write((char*)&(size), sizeof(int64_t));
+    out.write(metadata_.data(), size);
+  }
+}
+
+void FastText::loadMetadata(std::istream& in) {
+  metadata_.clear();
+  // The magic is read from the buffer, so that a shorter trailer leaves the
+  // stream as it was, even when it throws on short reads.
+  int32_t magic;
+  if (in.rdbuf()->sgetn((char*)&(magic), sizeof(int32_t)) !=
+          sizeof(int32_t) ||
+      magic != FASTTEXT_METADATA_MAGIC_INT32) {
+    // Whatever follows the model isn't ours to interpret.
+    return;
+  }
+  int64_t size;
+  in.read((char*)&(size), sizeof(int64_t));
+  if (!in || size < 0 || size > FASTTEXT_METADATA_MAX_SIZE) {
+    throw std::invalid_argument("Invalid model file: bad metadata size.");
+  }
+  std::string metadata(size, '\0');
+  in.read(&metadata[0], size);
+  if (in.gcount() != size) {
+    throw std::invalid_argument("Invalid model file: truncated metadata.");
+  }
+  metadata_ = metadata;
 }
 
 void FastText::loadModel(const std::string& filename) {
@@ -237,10 +286,23 @@ void FastText::buildModel() {
 }
 
 void FastText::loadModel(std::istream& in) {
+  loadModel(in, nullptr);
+}
+
+void FastText::loadModel(
+    std::istream& in,
+    const std::shared_ptr<MappedFile>& mapping) {
   args_ = std::make_shared<Args>();
   input_ = std::make_shared<DenseMatrix>();
   output_ = std::make_shared<DenseMatrix>();
   args_->load(in);
+  // Args::load copies the enums from the file as is.
+  if (args_->loss < loss_name::hs || args_->loss > loss_name::ova) {
+    throw std::invalid_argument("Invalid model file: unknown loss.");
+  }
+  if (args_->model < model_name::cbow || args_->model > model_name::sup) {
+    throw std::invalid_argument("Invalid model file: unknown model.");
+  }
   if (version == 11 && args_->model == model_name::sup) {
     // backward compatibility: old supervised models do not use char ngrams.
     args_->maxn = 0;
@@ -253,7 +315,16 @@ void FastText::loadModel(std::istream& in) {
     quant_ = true;
     input_ = std::make_shared<QuantMatrix>();
   }
-  input_->load(in);
+  if (mapping) {
+    if (quant_input) {
+      throw DenseMatrix::NotMappableError(
+          "quantized (.ftz) models can't be memory-mapped, use load_model "
+          "instead.");
+    }
+    std::dynamic_pointer_cast<DenseMatrix>(input_)->load(in, mapping);
+  } else {
+    input_->load(in);
+  }
 
   if (!quant_input && dict_->isPruned()) {
     throw std::invalid_argument(
@@ -269,6 +340,7 @@ void FastText::loadModel(std::istream& in) {
   output_->load(in);
 
   buildModel();
+  loadMetadata(in);
 }
 
 class MemoryBuffer : public std::streambuf {
@@ -277,6 +349,20 @@ public:
         char* p = const_cast<char*>(base);
         this->setg(p, p, p + size);
     }
+
+protected:
+    pos_type seekoff(off_type off, std::ios_base::seekdir dir, std::ios_base::openmode) override {
+        char* from = dir == std::ios_base::beg ? eback() : dir == std::ios_base::cur ? gptr() : egptr();
+        if (off < eback() - from || off > egptr() - from) {
+            return pos_type(off_type(-1));
+        }
+        setg(eback(), from + off, egptr());
+        return pos_type(gptr() - eback());
+    }
+
+    pos_type seekpos(pos_type pos, std::ios_base::openmode which) override {
+        return seekoff(off_type(pos), std::ios_base::beg, which);
+    }
 };
 
 void FastText::loadModelFromBuffer(const void* data, size_t size) {
@@ -289,6 +375,42 @@ void FastText::loadModelFromBuffer(const void* data, size_t size) {
     loadModel(in);
 }
 
+void FastText::loadModelFromStream(std::istream& in) {
+  if (!checkModel(in)) {
+    throw std::invalid_argument("Model from stream has wrong file format!");
+  }
+  // loadModel doesn't check its reads, and would go on with garbage sizes
+  // from a truncated stream.
+  const std::ios::iostate exceptions = in.exceptions();
+  in.exceptions(std::ios::failbit | std::ios::badbit);
+  try {
+    loadModel(in);
+  } catch (...) {
+    const bool truncated = in.fail();
+    in.exceptions(exceptions);
+    if (truncated) {
+      throw std::invalid_argument("Model from stream is truncated!");
+    }
+    throw;
+  }
+  in.exceptions(exceptions);
+}
+
+void FastText::loadModelMapped(const std::string& filename) {
+  try {
+    auto mapping = std::make_shared<MappedFile>(filename);
+    MemoryBuffer membuf(mapping->data(), mapping->size());
+    std::istream in(&membuf);
+
+    if (!checkModel(in)) {
+      throw std::invalid_argument(filename + " has wrong file format!");
+    }
+    loadModel(in, mapping);
+  } catch (const DenseMatrix::NotMappableError& e) {
+    throw DenseMatrix::NotMappableError(filename + ": " + e.what());
+  }
+}
+
 std::tuple<int64_t, double, double> FastText::progressInfo(real progress) {
   double t = utils::getDuration(start_, std::chrono::steady_clock::now());
   double lr = args_->lr * (1.0 - progress);
@@ -339,10 +461,18 @@ std::vector<int32_t> FastText::selectEmbeddings(int32_t cutoff) const {
 }
 
 void FastText::quantize(const Args& qargs, const TrainCallback& callback) {
+  if (!args_ || !input_ || !output_) {
+    throw std::runtime_error("Model never trained");
+  }
+  if (quant_) {
+    throw std::invalid_argument("Model is already quantized");
+  }
   if (args_->model != model_name::sup) {
     throw std::invalid_argument(
         "For now we only support quantization of supervised models");
   }
+  // The metadata describes the model as trained, which quantizing changes.
+  metadata_.clear();
   args_->input = qargs.input;
   args_->qout = qargs.qout;
   args_->output = qargs.output;
@@ -351,6 +481,8 @@ void FastText::quantize(const Args& qargs, const TrainCallback& callback) {
   std::shared_ptr<DenseMatrix> output =
       std::dynamic_pointer_cast<DenseMatrix>(output_);
   bool normalizeGradient = (args_->model == model_name::sup);
+  // A memory-mapped matrix is read-only, and quantizing normalizes it in place.
+  input->detach();
 
   if (qargs.cutoff > 0 && qargs.cutoff < input->size(0)) {
     auto idx = selectEmbeddings(qargs.cutoff);
@@ -544,9 +676,10 @@ std::vector<std::pair<std::string, Vector>> FastText::getNgramVectors(
   std::vector<int32_t> ngrams;
   std::vector<std::string> substrings;
   dict_->getSubwords(word, ngrams, substrings);
-  assert(ngrams.size() <= substrings.size());
+  assert(ngrams.size() == substrings.size());
   for (int32_t i = 0; i < ngrams.size(); i++) {
     Vector vec(args_->dim);
+    vec.zero();
     if (ngrams[i] >= 0) {
       vec.addRow(*input_, ngrams[i]);
     }
@@ -760,6 +893,7 @@ std::shared_ptr<Matrix> FastText::createTrainOutputMatrix() const {
 
 void FastText::train(const Args& args, const TrainCallback& callback) {
   args_ = std::make_shared<Args>(args);
+  metadata_.clear();
   dict_ = std::make_shared<Dictionary>(args_);
   if (args_->input == "-") {
     // manage expectations
@@ -47,11 +47,14 @@ class FastText {
   std::chrono::steady_clock::time_point start_;
   bool quant_;
   int32_t version;
+  std::string metadata_;
   std::unique_ptr<DenseMatrix> wordVectors_;
   std::exception_ptr trainException_;
 
   void signModel(std::ostream&);
   bool checkModel(std::istream&);
+  void loadMetadata(std::istream&);
+  void loadModel(std::istream&, const std::shared_ptr<MappedFile>& mapping);
   void startThreads(const TrainCallback& callback = {});
   void addInputVector(Vector&, int32_t) const;
   void trainThread(int32_t, const TrainCallback& callback);
@@ -98,6 +101,11 @@ class FastText {
     addInputVector(vec, ind);
   }
 
+  inline void getOutputVector(Vector& vec, int32_t ind) const {
+    vec.zero();
+    vec.addRow(*output_, ind);
+  }
+
   const Args getArgs() const;
 
   std::shared_ptr<const Dictionary> getDictionary() const;
@@ -110,10 +118,16 @@ class FastText {
 
   std::shared_ptr<const DenseMatrix> getOutputMatrix() const;
 
+  const std::string& getMetadata() const;
+
+  void setMetadata(const std::string& metadata);
+
   void saveVectors(const std::string& filename);
 
   void saveModel(const std::string& filename);
 
+  void saveModel(std::ostream& out);
+
   void saveOutput(const std::string& filename);
 
   void loadModel(std::istream& in);
@@ -122,6 +136,10 @@ class FastText {
 
   void loadModelFromBuffer(const void* data, size_t size);
 
+  void loadModelFromStream(std::istream& in);
+
+  void loadModelMapped(const std::string& filename);
+
   void getSentenceVector(std::istream& in, Vector& vec);
 
   void quantize(const Args& qargs, const TrainCallback& callback = {});
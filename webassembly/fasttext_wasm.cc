@@ -244,7 +244,9 @@ EMSCRIPTEN_BINDINGS(fasttext) {
       .function("getSubwords", &getSubwords, allow_raw_pointers())
       .function("getInputVector", &getInputVector, allow_raw_pointers())
       .function("train", &train, allow_raw_pointers())
-      .function("saveModel", &FastText::saveModel)
+      .function(
+          "saveModel",
+          select_overload<void(const std::string&)>(&FastText::saveModel))
       .property("isQuant", &FastText::isQuant)
       .property("args", &FastText::getArgs);
 